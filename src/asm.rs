@@ -0,0 +1,90 @@
+use std::str::FromStr;
+
+/// Bytes per line of [`Dialect::bytes`].
+const BYTES_PER_LINE: usize = 16;
+/// Words per line of [`Dialect::words`].
+const WORDS_PER_LINE: usize = 8;
+
+/// Syntax of the assembler compiled text is written for.
+///
+/// Every dialect takes `name:` labels and `$` hexadecimal literals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Asar,
+    Ca65,
+    WlaDx,
+    Bass,
+    /// A `.db` line of space-separated bytes per label, the original output of
+    /// `compile list`. Words are written as their little-endian bytes.
+    Plain,
+}
+
+impl Dialect {
+    /// Directives declaring bytes and 16-bit words, if there is one for
+    /// words, and the separator of their values.
+    fn directives(self) -> (&'static str, Option<&'static str>, &'static str) {
+        match self {
+            Dialect::Asar => ("db", Some("dw"), ","),
+            Dialect::Ca65 => (".byte", Some(".word"), ", "),
+            Dialect::WlaDx => (".db", Some(".dw"), ", "),
+            Dialect::Bass => ("db", Some("dw"), ","),
+            Dialect::Plain => (".db", None, " "),
+        }
+    }
+
+    /// Lines declaring `bytes`.
+    pub fn bytes(self, bytes: &[u8]) -> String {
+        let (db, _, separator) = self.directives();
+        let (indent, per_line) = match self {
+            Dialect::Plain => ("", bytes.len().max(1)),
+            _ => ("  ", BYTES_PER_LINE),
+        };
+        bytes
+            .chunks(per_line)
+            .map(|line| {
+                let values = line.iter().map(|byte| format!("${byte:02X}"));
+                format!(
+                    "{indent}{db} {}\n",
+                    values.collect::<Vec<_>>().join(separator)
+                )
+            })
+            .collect()
+    }
+
+    /// Lines declaring little-endian 16-bit `words`.
+    pub fn words(self, words: &[u16]) -> String {
+        let (_, dw, separator) = self.directives();
+        let Some(dw) = dw else {
+            return self.bytes(
+                &words
+                    .iter()
+                    .flat_map(|word| word.to_le_bytes())
+                    .collect::<Vec<_>>(),
+            );
+        };
+        words
+            .chunks(WORDS_PER_LINE)
+            .map(|line| {
+                let values = line.iter().map(|word| format!("${word:04X}"));
+                format!("  {dw} {}\n", values.collect::<Vec<_>>().join(separator))
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "asar" => Ok(Dialect::Asar),
+            "ca65" => Ok(Dialect::Ca65),
+            "wla" | "wla-dx" | "wladx" => Ok(Dialect::WlaDx),
+            "bass" => Ok(Dialect::Bass),
+            "plain" => Ok(Dialect::Plain),
+            _ => Err(format!(
+                "unknown assembler `{s}`, expected `asar`, `ca65`, `wla-dx`, `bass` or `plain`"
+            )),
+        }
+    }
+}
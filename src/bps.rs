@@ -0,0 +1,268 @@
+use crate::crc32::crc32;
+use crate::error::Error;
+
+const MAGIC: &[u8] = b"BPS1";
+const SOURCE_READ: usize = 0;
+const TARGET_READ: usize = 1;
+const SOURCE_COPY: usize = 2;
+/// Shortest unchanged run written as a `SourceRead` rather than being part of
+/// the surrounding `TargetRead`.
+const MIN_SOURCE_READ: usize = 4;
+
+/// Creates a BPS patch turning `source` into `target`.
+///
+/// Changed bytes are written as they are, without looking for data that moved.
+pub fn create_bps(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut patch = MAGIC.to_vec();
+    write_number(&mut patch, source.len());
+    write_number(&mut patch, target.len());
+    write_number(&mut patch, 0);
+
+    let same = |i: usize| source.get(i) == Some(&target[i]);
+    let mut i = 0;
+    while i < target.len() {
+        let unchanged = (i..target.len()).take_while(|&j| same(j)).count();
+        if unchanged >= MIN_SOURCE_READ || (unchanged > 0 && i + unchanged == target.len()) {
+            write_number(&mut patch, (unchanged - 1) << 2 | SOURCE_READ);
+            i += unchanged;
+            continue;
+        }
+
+        // up to the next unchanged run long enough for a SourceRead
+        let mut end = i + unchanged.max(1);
+        while end < target.len() {
+            let unchanged = (end..target.len()).take_while(|&j| same(j)).count();
+            if unchanged >= MIN_SOURCE_READ || (unchanged > 0 && end + unchanged == target.len()) {
+                break;
+            }
+            end += unchanged.max(1);
+        }
+        write_number(&mut patch, (end - i - 1) << 2 | TARGET_READ);
+        patch.extend(&target[i..end]);
+        i = end;
+    }
+
+    patch.extend(crc32(source).to_le_bytes());
+    patch.extend(crc32(target).to_le_bytes());
+    patch.extend(crc32(&patch).to_le_bytes());
+    patch
+}
+
+/// Applies a BPS patch to `source`, checking the CRC32 of the source, of the
+/// result and of the patch itself.
+pub fn apply_bps(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, Error> {
+    let invalid = |message: &str| Error::InvalidPatch(message.into());
+    if patch.len() < MAGIC.len() + 12 || !patch.starts_with(MAGIC) {
+        return Err(invalid("not a BPS patch"));
+    }
+
+    let (actions, footer) = patch.split_at(patch.len() - 12);
+    let crc =
+        |index: usize| u32::from_le_bytes(footer[index * 4..index * 4 + 4].try_into().unwrap());
+    if crc32(&patch[..patch.len() - 4]) != crc(2) {
+        return Err(invalid("the patch is corrupted"));
+    }
+    if crc32(source) != crc(0) {
+        return Err(Error::InvalidPatch(format!(
+            "the patch is for a ROM with CRC32 {:08X}, not {:08X}",
+            crc(0),
+            crc32(source)
+        )));
+    }
+
+    let mut reader = Reader {
+        data: actions,
+        position: MAGIC.len(),
+    };
+    let source_size = reader.number()?;
+    let target_size = reader.number()?;
+    let metadata = reader.number()?;
+    reader.position += metadata;
+    if source_size != source.len() {
+        return Err(invalid("the source size doesn't match the ROM"));
+    }
+
+    let mut target = Vec::with_capacity(target_size);
+    let mut source_offset = 0isize;
+    let mut target_offset = 0isize;
+    while reader.position < actions.len() {
+        let action = reader.number()?;
+        let length = (action >> 2) + 1;
+        let start = target.len();
+        let out_of_range = || invalid("an action reads outside of its data");
+
+        match action & 3 {
+            SOURCE_READ => {
+                target.extend(source.get(start..start + length).ok_or_else(out_of_range)?);
+            }
+            TARGET_READ => {
+                let bytes = actions
+                    .get(reader.position..reader.position + length)
+                    .ok_or_else(out_of_range)?;
+                target.extend(bytes);
+                reader.position += length;
+            }
+            SOURCE_COPY => {
+                source_offset += reader.signed()?;
+                let from = usize::try_from(source_offset).map_err(|_| out_of_range())?;
+                target.extend(source.get(from..from + length).ok_or_else(out_of_range)?);
+                source_offset += length as isize;
+            }
+            // TargetCopy
+            _ => {
+                target_offset += reader.signed()?;
+                let from = usize::try_from(target_offset).map_err(|_| out_of_range())?;
+                if from >= target.len() {
+                    return Err(out_of_range());
+                }
+                // the copy can overlap the bytes it writes
+                for i in from..from + length {
+                    target.push(target[i]);
+                }
+                target_offset += length as isize;
+            }
+        }
+    }
+
+    if target.len() != target_size || crc32(&target) != crc(1) {
+        return Err(invalid(
+            "the patched ROM doesn't match the one the patch was made for",
+        ));
+    }
+
+    Ok(target)
+}
+
+fn write_number(patch: &mut Vec<u8>, mut number: usize) {
+    loop {
+        let bits = (number & 0x7F) as u8;
+        number >>= 7;
+        if number == 0 {
+            patch.push(0x80 | bits);
+            return;
+        }
+        patch.push(bits);
+        number -= 1;
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn number(&mut self) -> Result<usize, Error> {
+        let too_large = || Error::InvalidPatch("a number is too large".into());
+        let mut number = 0usize;
+        let mut shift = 1usize;
+        loop {
+            let byte = *self.data.get(self.position).ok_or_else(|| {
+                Error::InvalidPatch("the patch ends in the middle of a number".into())
+            })?;
+            self.position += 1;
+            number = (byte as usize & 0x7F)
+                .checked_mul(shift)
+                .and_then(|bits| number.checked_add(bits))
+                .ok_or_else(too_large)?;
+            if byte & 0x80 != 0 {
+                return Ok(number);
+            }
+            shift = shift.checked_mul(0x80).ok_or_else(too_large)?;
+            number = number.checked_add(shift).ok_or_else(too_large)?;
+        }
+    }
+
+    /// A relative offset, with its sign in the lowest bit.
+    fn signed(&mut self) -> Result<isize, Error> {
+        let number = self.number()?;
+        let offset = (number >> 1) as isize;
+        Ok(if number & 1 != 0 { -offset } else { offset })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A patch made of `actions`, each followed by its own bytes.
+    fn patch(source: &[u8], target: &[u8], actions: &[(usize, &[u8])]) -> Vec<u8> {
+        let mut patch = MAGIC.to_vec();
+        write_number(&mut patch, source.len());
+        write_number(&mut patch, target.len());
+        write_number(&mut patch, 0);
+        for &(action, bytes) in actions {
+            write_number(&mut patch, action);
+            patch.extend(bytes);
+        }
+        patch.extend(crc32(source).to_le_bytes());
+        patch.extend(crc32(target).to_le_bytes());
+        patch.extend(crc32(&patch).to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn numbers() {
+        let mut data = vec![];
+        write_number(&mut data, 0x7F);
+        assert_eq!(data, [0xFF]);
+        data.clear();
+        write_number(&mut data, 0x80);
+        assert_eq!(data, [0x00, 0x80]);
+
+        for number in [0, 1, 0x7F, 0x80, 0x407F, 0x4080, 0x30_0000, usize::MAX >> 8] {
+            let mut data = vec![];
+            write_number(&mut data, number);
+            let mut reader = Reader {
+                data: &data,
+                position: 0,
+            };
+            assert_eq!(reader.number().unwrap(), number);
+            assert_eq!(reader.position, data.len());
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let source = (0..0x1000).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+        let mut target = source.clone();
+        target[0] ^= 0xFF;
+        target[0x10..0x12].fill(0);
+        target[0x800..0x900].fill(0xAA);
+        target.extend([1, 2, 3]);
+
+        for target in [&target[..], &target[..0x900], &source] {
+            let patch = create_bps(&source, target);
+            assert_eq!(apply_bps(&source, &patch).unwrap(), target);
+        }
+    }
+
+    #[test]
+    fn copies() {
+        let source = b"abcdef";
+        let target = b"defabcccc";
+        // signed offsets are doubled, with their sign in the lowest bit
+        let patch = patch(
+            source,
+            target,
+            &[
+                (2 << 2 | SOURCE_COPY, &[0x80 | 3 << 1]),
+                (2 << 2 | SOURCE_COPY, &[0x80 | 6 << 1 | 1]),
+                (2 << 2 | 3, &[0x80 | 5 << 1]),
+            ],
+        );
+        assert_eq!(apply_bps(source, &patch).unwrap(), target);
+    }
+
+    #[test]
+    fn checks() {
+        let source = vec![0; 0x100];
+        let target = vec![1; 0x100];
+        let mut patch = create_bps(&source, &target);
+        assert!(apply_bps(&target, &patch).is_err());
+
+        let length = patch.len();
+        patch[length - 13] ^= 1;
+        assert!(apply_bps(&source, &patch).is_err());
+    }
+}
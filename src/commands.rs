@@ -0,0 +1,284 @@
+use crate::dialogue::DialogueCommand;
+
+macro_rules! byte_enum {
+    ($name:ident { $($variant:ident = $value:expr),* $(,)? }) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($variant),*
+        }
+
+        impl $name {
+            pub fn from_byte(byte: u8) -> Option<Self> {
+                match byte {
+                    $($value => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            pub fn to_byte(self) -> u8 {
+                match self {
+                    $(Self::$variant => $value),*
+                }
+            }
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($variant)),*
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $(stringify!($variant) => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+byte_enum!(Colour {
+    Brown = 0x20,
+    White = 0x24,
+    Yellow = 0x28,
+    Green = 0x2C,
+});
+
+byte_enum!(Corner {
+    TopLeft = 0b00,
+    TopRight = 0b01,
+    BottomLeft = 0b10,
+    BottomRight = 0b11,
+});
+
+byte_enum!(DialogueKind {
+    Default = 0x00,
+    Ending = 0x03,
+    Village = 0x04,
+    Shop = 0x06,
+});
+
+byte_enum!(TextSpeed {
+    Fast = 0x84,
+    Slow = 0x89,
+});
+
+byte_enum!(Frame {
+    Top = 0x00,
+    Bottom = 0x01,
+});
+
+/// A command of the built-in opcodes, with typed arguments.
+///
+/// It converts from and to [`DialogueCommand::Command`], assuming its opcode
+/// has the meaning given by `opcodes.json`. Commands of opcodes redefined by
+/// a table are better read with [`DialogueCommand::arg`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    ClearFrame,
+    Unknown05,
+    SetColor(Colour),
+    Unknown0C(u8),
+    Unknown10(u8, u8, u8),
+    Unknown16,
+    Unknown17,
+    Unknown80,
+    Unknown81,
+    Unknown82,
+    ShowPortrait {
+        portrait: u8,
+        corner: Corner,
+        flags: u8,
+    },
+    CloseFrame {
+        corner: Corner,
+        flags: u8,
+    },
+    Unknown86,
+    Unknown87,
+    StartDialogue(DialogueKind),
+    PlaySong {
+        song: u8,
+        volume: u8,
+    },
+    WaitForA,
+    Unknown8B,
+    Unknown8C,
+    Unknown8D(u8, u8),
+    Unknown8E(u8, u8),
+    ChangeTextSpeed(TextSpeed),
+    Unknown90,
+    Unknown91,
+    SwitchFrame(Frame),
+    Unknown93,
+    TimedWaitForA(u16),
+    Unknown95,
+}
+
+impl Command {
+    /// The built-in command `command` is, or `None` for other commands and
+    /// for arguments the built-in opcode doesn't take.
+    pub fn from_dialogue(command: &DialogueCommand) -> Option<Self> {
+        let DialogueCommand::Command { opcode, args } = command else {
+            return None;
+        };
+        let byte = |index: usize| u8::try_from(args[index]).ok();
+
+        let command = match (*opcode, args.len()) {
+            (0x02, 0) => Command::ClearFrame,
+            (0x05, 0) => Command::Unknown05,
+            (0x07, 1) => Command::SetColor(Colour::from_byte(byte(0)?)?),
+            (0x0C, 1) => Command::Unknown0C(byte(0)?),
+            (0x10, 3) => Command::Unknown10(byte(0)?, byte(1)?, byte(2)?),
+            (0x16, 0) => Command::Unknown16,
+            (0x17, 0) => Command::Unknown17,
+            (0x80, 0) => Command::Unknown80,
+            (0x81, 0) => Command::Unknown81,
+            (0x82, 0) => Command::Unknown82,
+            (0x84, 3) => Command::ShowPortrait {
+                portrait: byte(0)?,
+                corner: Corner::from_byte(byte(1)?)?,
+                flags: byte(2)?,
+            },
+            (0x85, 2) => Command::CloseFrame {
+                corner: Corner::from_byte(byte(0)?)?,
+                flags: byte(1)?,
+            },
+            (0x86, 0) => Command::Unknown86,
+            (0x87, 0) => Command::Unknown87,
+            (0x88, 1) => Command::StartDialogue(DialogueKind::from_byte(byte(0)?)?),
+            (0x89, 2) => Command::PlaySong {
+                song: byte(0)?,
+                volume: byte(1)?,
+            },
+            (0x8A, 0) => Command::WaitForA,
+            (0x8B, 0) => Command::Unknown8B,
+            (0x8C, 0) => Command::Unknown8C,
+            (0x8D, 2) => Command::Unknown8D(byte(0)?, byte(1)?),
+            (0x8E, 2) => Command::Unknown8E(byte(0)?, byte(1)?),
+            (0x8F, 1) => Command::ChangeTextSpeed(TextSpeed::from_byte(byte(0)?)?),
+            (0x90, 0) => Command::Unknown90,
+            (0x91, 0) => Command::Unknown91,
+            (0x92, 1) => Command::SwitchFrame(Frame::from_byte(byte(0)?)?),
+            (0x93, 0) => Command::Unknown93,
+            (0x94, 1) => Command::TimedWaitForA(args[0]),
+            (0x95, 0) => Command::Unknown95,
+            _ => return None,
+        };
+        Some(command)
+    }
+}
+
+impl From<Command> for DialogueCommand {
+    fn from(command: Command) -> Self {
+        let (opcode, args) = match command {
+            Command::ClearFrame => (0x02, vec![]),
+            Command::Unknown05 => (0x05, vec![]),
+            Command::SetColor(colour) => (0x07, vec![colour.to_byte()]),
+            Command::Unknown0C(unk) => (0x0C, vec![unk]),
+            Command::Unknown10(unk1, unk2, unk3) => (0x10, vec![unk1, unk2, unk3]),
+            Command::Unknown16 => (0x16, vec![]),
+            Command::Unknown17 => (0x17, vec![]),
+            Command::Unknown80 => (0x80, vec![]),
+            Command::Unknown81 => (0x81, vec![]),
+            Command::Unknown82 => (0x82, vec![]),
+            Command::ShowPortrait {
+                portrait,
+                corner,
+                flags,
+            } => (0x84, vec![portrait, corner.to_byte(), flags]),
+            Command::CloseFrame { corner, flags } => (0x85, vec![corner.to_byte(), flags]),
+            Command::Unknown86 => (0x86, vec![]),
+            Command::Unknown87 => (0x87, vec![]),
+            Command::StartDialogue(kind) => (0x88, vec![kind.to_byte()]),
+            Command::PlaySong { song, volume } => (0x89, vec![song, volume]),
+            Command::WaitForA => (0x8A, vec![]),
+            Command::Unknown8B => (0x8B, vec![]),
+            Command::Unknown8C => (0x8C, vec![]),
+            Command::Unknown8D(unk1, unk2) => (0x8D, vec![unk1, unk2]),
+            Command::Unknown8E(unk1, unk2) => (0x8E, vec![unk1, unk2]),
+            Command::ChangeTextSpeed(speed) => (0x8F, vec![speed.to_byte()]),
+            Command::Unknown90 => (0x90, vec![]),
+            Command::Unknown91 => (0x91, vec![]),
+            Command::SwitchFrame(frame) => (0x92, vec![frame.to_byte()]),
+            Command::Unknown93 => (0x93, vec![]),
+            Command::TimedWaitForA(frames) => {
+                return DialogueCommand::Command {
+                    opcode: 0x94,
+                    args: vec![frames],
+                };
+            }
+            Command::Unknown95 => (0x95, vec![]),
+        };
+
+        DialogueCommand::Command {
+            opcode,
+            args: args.into_iter().map(u16::from).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialogue::decode_dialogue;
+    use crate::dialogue::encode_dialogue;
+    use crate::tables::Tables;
+
+    #[test]
+    fn matches_the_built_in_opcodes() {
+        let tables = Tables::default();
+        let commands = [
+            Command::ClearFrame,
+            Command::Unknown05,
+            Command::SetColor(Colour::Yellow),
+            Command::Unknown0C(0x12),
+            Command::Unknown10(1, 2, 3),
+            Command::Unknown16,
+            Command::Unknown17,
+            Command::Unknown80,
+            Command::Unknown81,
+            Command::Unknown82,
+            Command::ShowPortrait {
+                portrait: 0x05,
+                corner: Corner::BottomLeft,
+                flags: 0x2A,
+            },
+            Command::CloseFrame {
+                corner: Corner::TopRight,
+                flags: 0x01,
+            },
+            Command::Unknown86,
+            Command::Unknown87,
+            Command::StartDialogue(DialogueKind::Village),
+            Command::PlaySong {
+                song: 12,
+                volume: 3,
+            },
+            Command::WaitForA,
+            Command::Unknown8B,
+            Command::Unknown8C,
+            Command::Unknown8D(4, 5),
+            Command::Unknown8E(6, 7),
+            Command::ChangeTextSpeed(TextSpeed::Slow),
+            Command::Unknown90,
+            Command::Unknown91,
+            Command::SwitchFrame(Frame::Bottom),
+            Command::Unknown93,
+            Command::TimedWaitForA(300),
+            Command::Unknown95,
+        ];
+        assert_eq!(commands.len(), tables.opcodes.iter().count());
+
+        for command in commands {
+            let dialogue = DialogueCommand::from(command);
+            let name = format!("{command:?}");
+            let name = name.split(['(', ' ']).next().unwrap();
+            assert_eq!(dialogue.name(&tables.opcodes), Some(name));
+
+            let data = encode_dialogue(&[dialogue, DialogueCommand::End], &tables).unwrap();
+            let decoded = decode_dialogue(&data, &tables).unwrap();
+            assert_eq!(Command::from_dialogue(&decoded[0]), Some(command));
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use crate::error::Error;
+use crate::opcodes::ArgumentKind;
+use crate::opcodes::END;
+use crate::opcodes::NEW_LINE;
+use crate::opcodes::Opcode;
+use crate::opcodes::Opcodes;
+use crate::opcodes::PAGES;
+use crate::tables::Tables;
+use std::ops::Range;
+
+/// One element of a dialogue script.
+///
+/// Page switches (`0x11`-`0x14`) are not represented: they are implied by the
+/// characters of `Text` and inserted again by [`encode_dialogue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogueCommand {
+    Text(String),
+    /// A character whose glyph has not been identified yet. `page` is 0-3.
+    UnknownGlyph {
+        page: u8,
+        code: u8,
+    },
+    End,
+    NewLine,
+    /// Bytes written as they are, like a command missing from the opcodes.
+    /// The text following them starts with a page switch.
+    Raw(Vec<u8>),
+    /// A command described by the [`Opcode`] of `opcode`, with one value per
+    /// argument appearing in scripts (so without its fixed bytes). Built-in
+    /// opcodes convert from and to a typed [`Command`](crate::Command).
+    Command {
+        opcode: u8,
+        args: Vec<u16>,
+    },
+}
+
+impl DialogueCommand {
+    /// Makes the command called `name` in `opcodes`, like `ShowPortrait`, from
+    /// the value of each of its arguments by name. Returns `None` if there is no
+    /// such command, if an argument is missing or unknown, or if a value doesn't
+    /// fit its argument.
+    ///
+    /// The value of an enum argument can be found with [`Argument::value`](crate::opcodes::Argument::value):
+    ///
+    /// ```
+    /// # use fe3_text::DialogueCommand;
+    /// # use fe3_text::tables::Tables;
+    /// let opcodes = Tables::default().opcodes;
+    /// let (_, spec) = opcodes.find("SetColor").unwrap();
+    /// let yellow = spec.visible_args().next().unwrap().value("Yellow").unwrap();
+    /// let command = DialogueCommand::command(&opcodes, "SetColor", &[("colour", yellow)]);
+    /// assert_eq!(command.unwrap().arg_name(&opcodes, "colour"), Some("Yellow"));
+    /// ```
+    pub fn command(opcodes: &Opcodes, name: &str, args: &[(&str, u16)]) -> Option<Self> {
+        let (opcode, spec) = opcodes.find(name)?;
+        if args.len() != spec.visible_args().count() {
+            return None;
+        }
+        let values = spec
+            .visible_args()
+            .map(|arg| {
+                let &(_, value) = args.iter().find(|(name, _)| *name == arg.name)?;
+                let valid = match arg.kind {
+                    ArgumentKind::Enum { .. } => arg.value_name(value).is_some(),
+                    _ => value <= arg.max(),
+                };
+                valid.then_some(value)
+            })
+            .collect::<Option<_>>()?;
+
+        Some(DialogueCommand::Command {
+            opcode,
+            args: values,
+        })
+    }
+
+    /// The description of a `Command` in `opcodes`.
+    pub fn spec<'a>(&self, opcodes: &'a Opcodes) -> Option<&'a Opcode> {
+        match self {
+            DialogueCommand::Command { opcode, .. } => opcodes.get(*opcode),
+            _ => None,
+        }
+    }
+
+    /// The name of a `Command` in scripts, like `ShowPortrait`.
+    pub fn name<'a>(&self, opcodes: &'a Opcodes) -> Option<&'a str> {
+        self.spec(opcodes).map(|spec| spec.name.as_str())
+    }
+
+    /// The value of the argument called `name` of a `Command`, like the
+    /// `portrait` of `ShowPortrait`.
+    pub fn arg(&self, opcodes: &Opcodes, name: &str) -> Option<u16> {
+        let DialogueCommand::Command { args, .. } = self else {
+            return None;
+        };
+        args.get(self.spec(opcodes)?.arg_index(name)?).copied()
+    }
+
+    /// The name of the value of the enum argument called `name` of a
+    /// `Command`, like `TopLeft` for the `corner` of `ShowPortrait`.
+    pub fn arg_name<'a>(&self, opcodes: &'a Opcodes, name: &str) -> Option<&'a str> {
+        let spec = self.spec(opcodes)?;
+        let arg = spec.visible_args().nth(spec.arg_index(name)?)?;
+        arg.value_name(self.arg(opcodes, name)?)
+    }
+}
+
+/// Decodes a dialogue script up to and including its `[End]` command.
+pub fn decode_dialogue(data: &[u8], tables: &Tables) -> Result<Vec<DialogueCommand>, Error> {
+    Ok(decode_dialogue_spans(data, tables)?
+        .into_iter()
+        .map(|(_, command)| command)
+        .collect())
+}
+
+/// Same as [`decode_dialogue`], but also returns the bytes each command was decoded from.
+///
+/// A page switch belongs to the text it occurs in, or else to the command that follows it.
+pub fn decode_dialogue_spans(
+    data: &[u8],
+    tables: &Tables,
+) -> Result<Vec<(Range<usize>, DialogueCommand)>, Error> {
+    decode(data, tables, false)
+}
+
+/// Same as [`decode_dialogue_spans`], but an unknown command or an invalid
+/// argument becomes [`DialogueCommand::Raw`] bytes instead of an error, so
+/// that any script up to an `[End]` can be decompiled.
+pub fn decode_dialogue_lenient(
+    data: &[u8],
+    tables: &Tables,
+) -> Result<Vec<(Range<usize>, DialogueCommand)>, Error> {
+    decode(data, tables, true)
+}
+
+fn decode(
+    data: &[u8],
+    tables: &Tables,
+    lenient: bool,
+) -> Result<Vec<(Range<usize>, DialogueCommand)>, Error> {
+    let mut commands: Vec<(Range<usize>, DialogueCommand)> = vec![];
+    let mut rom = Reader {
+        data,
+        position: 0,
+        start: 0,
+    };
+    let mut page = 0;
+    let mut page_switch = None;
+    // after raw bytes, characters need a page switch to be encoded the same
+    let mut after_raw = false;
+
+    loop {
+        rom.start = rom.position;
+        let start = rom.start;
+        let id = rom.u8()?;
+
+        if id != 0 {
+            let start = page_switch.take().unwrap_or(start);
+            let c = tables.dialogue[page][id as usize];
+            if after_raw {
+                match commands.last_mut() {
+                    Some((span, DialogueCommand::Raw(bytes))) if span.end == start => {
+                        bytes.push(id);
+                        span.end = rom.position;
+                    }
+                    _ => commands.push((start..rom.position, DialogueCommand::Raw(vec![id]))),
+                }
+            } else if c == '_' {
+                let glyph = DialogueCommand::UnknownGlyph {
+                    page: page as u8,
+                    code: id,
+                };
+                commands.push((start..rom.position, glyph));
+            } else if let Some((span, DialogueCommand::Text(text))) = commands.last_mut()
+                && span.end == start
+            {
+                text.push(c);
+                span.end = rom.position;
+            } else {
+                commands.push((start..rom.position, DialogueCommand::Text(c.into())));
+            }
+            continue;
+        }
+
+        let opcode = rom.u8()?;
+        let command = match opcode {
+            END => DialogueCommand::End,
+            NEW_LINE => DialogueCommand::NewLine,
+            _ if PAGES.contains(&opcode) => {
+                page = (opcode - PAGES.start()) as usize;
+                after_raw = false;
+                // a switch in the middle of a text stays part of that text
+                match commands.last_mut() {
+                    Some((span, DialogueCommand::Text(_))) if span.end == start => {
+                        span.end = rom.position
+                    }
+                    _ => page_switch = Some(page_switch.unwrap_or(start)),
+                }
+                continue;
+            }
+            _ => {
+                let command = match tables.opcodes.get(opcode) {
+                    Some(spec) => rom
+                        .args(opcode, spec)
+                        .map(|args| DialogueCommand::Command { opcode, args }),
+                    None => Err(Error::UnknownOpcode {
+                        offset: start + 1,
+                        opcode,
+                    }),
+                };
+                match command {
+                    Ok(command) => command,
+                    // the bytes read so far, the rest being decoded as usual
+                    Err(Error::UnknownOpcode { .. } | Error::InvalidValue { .. }) if lenient => {
+                        after_raw = true;
+                        DialogueCommand::Raw(data[start..rom.position].to_vec())
+                    }
+                    Err(error) => return Err(error),
+                }
+            }
+        };
+
+        let end = command == DialogueCommand::End;
+        let start = page_switch.take().unwrap_or(start);
+        commands.push((start..rom.position, command));
+        if end {
+            return Ok(commands);
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+    /// Start of the command being read, for error reporting.
+    start: usize,
+}
+
+impl Reader<'_> {
+    fn u8(&mut self) -> Result<u8, Error> {
+        let byte = *self
+            .data
+            .get(self.position)
+            .ok_or(Error::UnexpectedEnd { offset: self.start })?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    /// Reads the arguments of a command, checking fixed bytes and enum values.
+    fn args(&mut self, opcode: u8, spec: &Opcode) -> Result<Vec<u16>, Error> {
+        let mut args = vec![];
+        let mut packed = 0;
+
+        for arg in &spec.args {
+            let value = match (arg.bits, &arg.kind) {
+                (None, ArgumentKind::U16) => u16::from_le_bytes([self.u8()?, self.u8()?]),
+                (None, _) => self.u8()? as u16,
+                (Some([shift, _]), _) => {
+                    if shift == 0 {
+                        packed = self.u8()?;
+                    }
+                    (packed >> shift) as u16 & arg.max()
+                }
+            };
+            let invalid = Error::InvalidValue {
+                offset: self.position - 1,
+                opcode,
+                value: value as u8,
+            };
+
+            match &arg.kind {
+                ArgumentKind::Fixed { value: fixed } if fixed.0 as u16 != value => {
+                    return Err(invalid);
+                }
+                ArgumentKind::Fixed { .. } => continue,
+                ArgumentKind::Enum { values } if !values.values().any(|v| v.0 as u16 == value) => {
+                    return Err(invalid);
+                }
+                _ => args.push(value),
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Encodes a dialogue script, inserting page switches where `Text` needs them.
+pub fn encode_dialogue(commands: &[DialogueCommand], tables: &Tables) -> Result<Vec<u8>, Error> {
+    let mut output = vec![];
+    let mut current_page = Some(0);
+
+    for command in commands {
+        match command {
+            DialogueCommand::Text(text) => {
+                for c in text.chars() {
+                    let (page, code) = tables.find_glyph(c).ok_or(Error::UnmappedCharacter {
+                        location: None,
+                        character: c,
+                    })?;
+                    switch_page(&mut output, &mut current_page, page);
+                    output.push(code);
+                }
+            }
+            DialogueCommand::UnknownGlyph { page, code } => {
+                switch_page(&mut output, &mut current_page, *page);
+                output.push(*code);
+            }
+            DialogueCommand::End => output.extend([0x00, END]),
+            DialogueCommand::NewLine => output.extend([0x00, NEW_LINE]),
+            DialogueCommand::Raw(bytes) => {
+                output.extend(bytes);
+                // the bytes may switch page or be a command that forgets it
+                current_page = None;
+            }
+            DialogueCommand::Command { opcode, args } => {
+                let invalid = |message: String| Error::InvalidCommand {
+                    opcode: *opcode,
+                    message,
+                };
+                let spec = tables
+                    .opcodes
+                    .get(*opcode)
+                    .ok_or_else(|| invalid("unknown opcode".into()))?;
+                if args.len() != spec.visible_args().count() {
+                    return Err(invalid("wrong number of arguments".into()));
+                }
+
+                output.extend([0x00, *opcode]);
+                let mut args = args.iter();
+                let mut packed = None;
+                for arg in &spec.args {
+                    let value = match &arg.kind {
+                        ArgumentKind::Fixed { value } => value.0 as u16,
+                        _ => *args.next().unwrap(),
+                    };
+                    if value > arg.max() {
+                        return Err(invalid(format!("{value} is too big for `{}`", arg.name)));
+                    }
+
+                    match arg.bits {
+                        Some([0, _]) => output.extend(packed.replace(value as u8)),
+                        Some([shift, _]) => *packed.get_or_insert(0) |= (value as u8) << shift,
+                        None => {
+                            output.extend(packed.take());
+                            match arg.kind {
+                                ArgumentKind::U16 => output.extend(value.to_le_bytes()),
+                                _ => output.push(value as u8),
+                            }
+                        }
+                    }
+                }
+                output.extend(packed);
+
+                if spec.resets_page {
+                    current_page = None;
+                }
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+fn switch_page(output: &mut Vec<u8>, current_page: &mut Option<u8>, page: u8) {
+    if *current_page != Some(page) {
+        output.extend([0x00, page + PAGES.start()]);
+        *current_page = Some(page);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::script::BOM;
+use std::fmt;
+use std::io;
+
+/// A position in a text source, both 1-based. Columns count characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Json(serde_json::Error),
+    InvalidTable(String),
+    InvalidProfile(String),
+    InvalidManifest(String),
+    InvalidPatch(String),
+    /// A `{` in a string of a list that doesn't start an escape like `{0x0147}`,
+    /// or escapes a control code.
+    InvalidEscape(String),
+    /// The data ended before the end of the script or command starting at `offset`.
+    UnexpectedEnd {
+        offset: usize,
+    },
+    UnknownOpcode {
+        offset: usize,
+        opcode: u8,
+    },
+    /// An argument byte of `opcode` at `offset` has a value the command doesn't accept.
+    InvalidValue {
+        offset: usize,
+        opcode: u8,
+        value: u8,
+    },
+    /// The pointer at `pointer` can't be made to point to `target`.
+    UnreachableTarget {
+        pointer: usize,
+        target: usize,
+    },
+    /// A `Command` that doesn't match the description of its opcode.
+    InvalidCommand {
+        opcode: u8,
+        message: String,
+    },
+    Syntax {
+        location: Location,
+        message: String,
+    },
+    UnknownCommand {
+        location: Location,
+        name: String,
+    },
+    ArgumentCount {
+        location: Location,
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    /// `value` can't be used as `argument` of the command `name`.
+    InvalidArgument {
+        location: Location,
+        name: String,
+        argument: String,
+        value: String,
+    },
+    UnknownPortrait {
+        location: Location,
+        name: String,
+    },
+    /// `character` has no glyph in the font. The location is missing when the
+    /// text didn't come from a script.
+    UnmappedCharacter {
+        location: Option<Location>,
+        character: char,
+    },
+}
+
+impl Error {
+    /// Where the error is in the script, for errors coming from a script.
+    pub fn location(&self) -> Option<Location> {
+        match *self {
+            Error::Syntax { location, .. }
+            | Error::UnknownCommand { location, .. }
+            | Error::ArgumentCount { location, .. }
+            | Error::InvalidArgument { location, .. }
+            | Error::UnknownPortrait { location, .. } => Some(location),
+            Error::UnmappedCharacter { location, .. } => location,
+            _ => None,
+        }
+    }
+
+    /// Where the error is in the ROM, for decoding errors.
+    pub fn offset(&self) -> Option<usize> {
+        match *self {
+            Error::UnexpectedEnd { offset }
+            | Error::UnknownOpcode { offset, .. }
+            | Error::InvalidValue { offset, .. }
+            | Error::UnreachableTarget {
+                pointer: offset, ..
+            } => Some(offset),
+            _ => None,
+        }
+    }
+
+    /// Describes the error, without its location in a script.
+    fn message(&self) -> String {
+        match self {
+            Error::Io(error) => error.to_string(),
+            Error::Json(error) => format!("invalid JSON: {error}"),
+            Error::InvalidTable(message) => format!("invalid table: {message}"),
+            Error::InvalidProfile(message) => format!("invalid profile: {message}"),
+            Error::InvalidManifest(message) => format!("invalid manifest: {message}"),
+            Error::InvalidPatch(message) => format!("invalid patch: {message}"),
+            Error::UnexpectedEnd { offset } => {
+                format!("{offset:#X}: data ends in the middle of a script")
+            }
+            Error::UnknownOpcode { offset, opcode } => {
+                format!("{offset:#X}: unknown command {opcode:#04X}")
+            }
+            Error::InvalidValue {
+                offset,
+                opcode,
+                value,
+            } => format!("{offset:#X}: invalid value {value:#04X} for command {opcode:#04X}"),
+            Error::InvalidEscape(escape) => {
+                format!("`{escape}` is not an escape like `{{0x0147}}` below 0xFFFD")
+            }
+            Error::UnreachableTarget { pointer, target } => {
+                format!("{pointer:#X}: a pointer there can't reach {target:#X}")
+            }
+            Error::InvalidCommand { opcode, message } => {
+                format!("invalid command {opcode:#04X}: {message}")
+            }
+            Error::Syntax { message, .. } => message.clone(),
+            Error::UnknownCommand { name, .. } => format!("unknown command `{name}`"),
+            Error::ArgumentCount {
+                name,
+                expected,
+                found,
+                ..
+            } => format!("`{name}` takes {expected} argument(s) but {found} were given"),
+            Error::InvalidArgument {
+                name,
+                argument,
+                value,
+                ..
+            } => format!("invalid {argument} `{value}` for `{name}`"),
+            Error::UnknownPortrait { name, .. } => format!("unknown portrait `{name}`"),
+            Error::UnmappedCharacter { character, .. } => format!("no glyph for {character:?}"),
+        }
+    }
+
+    /// Formats the error like a compiler would, quoting the line of `source`
+    /// it comes from and underlining the offending text.
+    pub fn diagnostic(&self, filename: &str, source: &str) -> String {
+        let Some(location) = self.location() else {
+            return format!("error: {self}");
+        };
+
+        let message = self.message();
+        let underlined = match self {
+            Error::UnknownCommand { name, .. } | Error::ArgumentCount { name, .. } => name.clone(),
+            Error::InvalidArgument { value, .. } => value.clone(),
+            Error::UnknownPortrait { name, .. } => name.clone(),
+            Error::UnmappedCharacter { character, .. } => character.to_string(),
+            _ => String::new(),
+        };
+
+        let source = source.strip_prefix(BOM).unwrap_or(source);
+        let line = source.lines().nth(location.line - 1).unwrap_or_default();
+        let number = location.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let indent = line
+            .chars()
+            .take(location.column - 1)
+            .map(|c| match c {
+                '\t' => "\t",
+                c if is_wide(c) => "  ",
+                _ => " ",
+            })
+            .collect::<String>();
+        let width = underlined
+            .chars()
+            .map(|c| if is_wide(c) { 2 } else { 1 })
+            .sum::<usize>();
+        let carets = "^".repeat(width.max(1));
+
+        format!(
+            "error: {message}\n{gutter}--> {filename}:{location}\n{gutter} |\n{number} | {line}\n{gutter} | {indent}{carets}"
+        )
+    }
+
+    /// Shifts the ROM offset of a decoding error by `base`, for data that was
+    /// decoded from a slice of the ROM.
+    pub fn rebase(self, base: usize) -> Self {
+        match self {
+            Error::UnexpectedEnd { offset } => Error::UnexpectedEnd {
+                offset: offset + base,
+            },
+            Error::UnknownOpcode { offset, opcode } => Error::UnknownOpcode {
+                offset: offset + base,
+                opcode,
+            },
+            Error::InvalidValue {
+                offset,
+                opcode,
+                value,
+            } => Error::InvalidValue {
+                offset: offset + base,
+                opcode,
+                value,
+            },
+            error => error,
+        }
+    }
+}
+
+/// Whether a terminal displays `c` over two columns, as it does for most Japanese text.
+fn is_wide(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+    )
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location() {
+            Some(location) => write!(f, "{location}: {}", self.message()),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
@@ -0,0 +1,200 @@
+use crate::error::Error;
+use std::ops::Range;
+
+const HEADER: &[u8] = b"PATCH";
+const FOOTER: &[u8] = b"EOF";
+/// A record can't start here: its offset would be read as [`FOOTER`].
+const EOF_OFFSET: usize = 0x454F46;
+/// Offsets and the truncated size are 24-bit.
+const MAX_OFFSET: usize = 0xFFFFFF;
+const MAX_RECORD: usize = 0xFFFF;
+/// Shortest run of a byte written as an RLE record, which takes 8 bytes.
+const MIN_RLE: usize = 9;
+/// Unchanged bytes between two changes that are still written, rather than
+/// starting a new record with its 5 bytes of header.
+const MAX_GAP: usize = 5;
+
+/// Creates an IPS patch turning `source` into `target`.
+///
+/// A `target` shorter than `source` is handled with the truncation extension:
+/// the new size follows the footer.
+pub fn create_ips(source: &[u8], target: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut patch = HEADER.to_vec();
+    let changed = |i: usize| source.get(i) != Some(&target[i]);
+
+    let mut i = 0;
+    while i < target.len() {
+        if !changed(i) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let mut end = i + 1;
+        while end < target.len() {
+            match (end..target.len().min(end + MAX_GAP + 1)).find(|&j| changed(j)) {
+                Some(j) => end = j + 1,
+                None => break,
+            }
+        }
+        write_records(&mut patch, target, start..end)?;
+        i = end;
+    }
+
+    patch.extend(FOOTER);
+    if target.len() < source.len() {
+        patch.extend(offset(target.len())?);
+    }
+
+    Ok(patch)
+}
+
+fn write_records(patch: &mut Vec<u8>, target: &[u8], range: Range<usize>) -> Result<(), Error> {
+    let run = |start: usize| {
+        target[start..range.end.min(start + MAX_RECORD)]
+            .iter()
+            .take_while(|&&byte| byte == target[start])
+            .count()
+    };
+
+    let mut start = range.start;
+    while start < range.end {
+        if start == EOF_OFFSET {
+            // write the previous byte again to start one byte earlier
+            patch.extend(offset(start - 1)?);
+            patch.extend(2u16.to_be_bytes());
+            patch.extend(&target[start - 1..=start]);
+            start += 1;
+            continue;
+        }
+
+        let length = run(start);
+        if length >= MIN_RLE {
+            patch.extend(offset(start)?);
+            patch.extend(0u16.to_be_bytes());
+            patch.extend((length as u16).to_be_bytes());
+            patch.push(target[start]);
+            start += length;
+            continue;
+        }
+
+        let mut end = start + 1;
+        while end < range.end && end - start < MAX_RECORD && end != EOF_OFFSET && run(end) < MIN_RLE
+        {
+            end += 1;
+        }
+        patch.extend(offset(start)?);
+        patch.extend(((end - start) as u16).to_be_bytes());
+        patch.extend(&target[start..end]);
+        start = end;
+    }
+
+    Ok(())
+}
+
+fn offset(offset: usize) -> Result<[u8; 3], Error> {
+    if offset > MAX_OFFSET {
+        return Err(Error::InvalidPatch(format!(
+            "offset {offset:#X} is beyond the 16 MiB an IPS patch can address"
+        )));
+    }
+    let [_, bytes @ ..] = (offset as u32).to_be_bytes();
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies `patch` the way patching tools do, with the truncation extension.
+    fn apply(source: &[u8], patch: &[u8]) -> Vec<u8> {
+        fn read(patch: &mut &[u8], length: usize) -> usize {
+            let (bytes, rest) = patch.split_at(length);
+            *patch = rest;
+            bytes
+                .iter()
+                .fold(0, |number, &byte| number << 8 | byte as usize)
+        }
+
+        let mut target = source.to_vec();
+        let mut patch = patch.strip_prefix(HEADER).unwrap();
+        loop {
+            let offset = read(&mut patch, 3);
+            if offset == EOF_OFFSET {
+                if !patch.is_empty() {
+                    target.truncate(read(&mut patch, 3));
+                }
+                assert!(patch.is_empty());
+                return target;
+            }
+            let bytes = match read(&mut patch, 2) {
+                0 => {
+                    let length = read(&mut patch, 2);
+                    vec![read(&mut patch, 1) as u8; length]
+                }
+                length => (0..length).map(|_| read(&mut patch, 1) as u8).collect(),
+            };
+            if target.len() < offset + bytes.len() {
+                target.resize(offset + bytes.len(), 0);
+            }
+            target[offset..offset + bytes.len()].copy_from_slice(&bytes);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let source = (0..0x1000).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+        let mut target = source.clone();
+        target[0x10] ^= 0xFF;
+        target[0x14] ^= 0xFF;
+        target[0x100..0x180].fill(0xAA);
+        target.extend([1, 2, 3]);
+
+        let patch = create_ips(&source, &target).unwrap();
+        assert_eq!(apply(&source, &patch), target);
+        assert_eq!(create_ips(&source, &source).unwrap(), b"PATCHEOF");
+    }
+
+    #[test]
+    fn runs_are_rle_records() {
+        let source = vec![0; 0x100];
+        let mut target = source.clone();
+        target[0x20..0x20 + MIN_RLE].fill(0x55);
+
+        let patch = create_ips(&source, &target).unwrap();
+        assert_eq!(patch, b"PATCH\x00\x00\x20\x00\x00\x00\x09\x55EOF");
+        assert_eq!(apply(&source, &patch), target);
+    }
+
+    #[test]
+    fn no_record_starts_at_eof() {
+        let source = vec![0; EOF_OFFSET + 0x10];
+        let mut target = source.clone();
+        target[EOF_OFFSET..EOF_OFFSET + 2].copy_from_slice(&[1, 2]);
+
+        // a record at `EOF_OFFSET` would end the patch there
+        let patch = create_ips(&source, &target).unwrap();
+        assert_eq!(apply(&source, &patch), target);
+    }
+
+    #[test]
+    fn truncation() {
+        let source = vec![1; 0x100];
+        let target = vec![1; 0x80];
+
+        let patch = create_ips(&source, &target).unwrap();
+        assert_eq!(patch, b"PATCHEOF\x00\x00\x80");
+        assert_eq!(apply(&source, &patch), target);
+    }
+
+    #[test]
+    fn offsets_are_24_bit() {
+        let source = vec![0; MAX_OFFSET + 2];
+        let mut target = source.clone();
+        target[MAX_OFFSET] = 1;
+        assert!(create_ips(&source, &target).is_ok());
+        target[MAX_OFFSET + 1] = 1;
+        target[MAX_OFFSET] = 0;
+        assert!(create_ips(&source, &target).is_err());
+    }
+}
@@ -0,0 +1,25 @@
+pub mod asm;
+pub mod bps;
+pub mod commands;
+pub mod crc32;
+pub mod dialogue;
+pub mod error;
+pub mod ips;
+pub mod list;
+pub mod opcodes;
+pub mod pointers;
+pub mod profiles;
+pub mod project;
+pub mod rom;
+pub mod scan;
+pub mod script;
+pub mod space;
+pub mod tables;
+
+pub use commands::Command;
+pub use dialogue::DialogueCommand;
+pub use dialogue::decode_dialogue;
+pub use dialogue::encode_dialogue;
+pub use error::Error;
+pub use script::format_dialogue;
+pub use script::parse_dialogue;
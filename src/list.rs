@@ -0,0 +1,115 @@
+use crate::error::Error;
+use crate::tables::Tables;
+
+pub const NEW_LINE: u16 = 0xFFFD;
+pub const CARRIAGE_RETURN: u16 = 0xFFFE; // "line break storage/memory"?
+pub const TERMINATOR: u16 = 0xFFFF;
+
+/// Decodes a list of little-endian, `0xFFFF`-terminated strings.
+///
+/// `0xFFFD` and `0xFFFE` become `'\n'` and `'\r'`. Codes without a glyph in
+/// `tables`, or with a `_` or `{`, are written as an escape like `{0x0147}`.
+/// If `data` ends before the terminator of the last string, that string is
+/// returned unless it's empty.
+pub fn decode_list(data: &[u8], tables: &Tables) -> Vec<String> {
+    let mut strings = vec![String::new()];
+
+    for word in data.chunks_exact(2) {
+        let data = u16::from_le_bytes([word[0], word[1]]);
+        let current = strings.last_mut().unwrap();
+        match data {
+            TERMINATOR => strings.push(String::new()),
+            CARRIAGE_RETURN => current.push('\r'),
+            NEW_LINE => current.push('\n'),
+            _ => match tables.list.get(data as usize) {
+                Some(&c) if c != '_' && c != '{' => current.push(c),
+                _ => current.push_str(&format!("{{0x{data:04X}}}")),
+            },
+        }
+    }
+    if strings.last().is_some_and(String::is_empty) {
+        strings.pop();
+    }
+
+    strings
+}
+
+/// Length in bytes of the first `count` strings of `data`, including their
+/// terminators, or `None` if `data` ends before.
+pub fn list_length(data: &[u8], count: usize) -> Option<usize> {
+    if count == 0 {
+        return Some(0);
+    }
+    data.chunks_exact(2)
+        .enumerate()
+        .filter(|(_, word)| u16::from_le_bytes([word[0], word[1]]) == TERMINATOR)
+        .nth(count - 1)
+        .map(|(index, _)| (index + 1) * 2)
+}
+
+/// Length in bytes of the strings of `data` up to the last terminator, the
+/// words after it not making a complete string.
+pub fn terminated_length(data: &[u8]) -> usize {
+    data.chunks_exact(2)
+        .rposition(|word| u16::from_le_bytes([word[0], word[1]]) == TERMINATOR)
+        .map_or(0, |index| (index + 1) * 2)
+}
+
+/// Length in bytes of the strings at the start of `data`, up to the first word
+/// that is neither a character of `tables` nor a control code, and the offset
+/// of that word if there is one. A string interrupted by that word isn't part
+/// of the list.
+pub fn detect_list_length(data: &[u8], tables: &Tables) -> (usize, Option<usize>) {
+    let mut length = 0;
+    for (offset, word) in (0..).step_by(2).zip(data.chunks_exact(2)) {
+        match u16::from_le_bytes([word[0], word[1]]) {
+            TERMINATOR => length = offset + 2,
+            NEW_LINE | CARRIAGE_RETURN => {}
+            code if (code as usize) < tables.list.len() => {}
+            _ => return (length, Some(offset)),
+        }
+    }
+    (length, None)
+}
+
+/// Encodes one string of a list, including its `0xFFFF` terminator.
+///
+/// An escape like `{0x0147}` is written as that code, which can't be one of
+/// the control codes from `0xFFFD`.
+pub fn encode_string(string: &str, tables: &Tables) -> Result<Vec<u16>, Error> {
+    let mut words = vec![];
+
+    let mut chars = string.char_indices();
+    while let Some((index, c)) = chars.next() {
+        let word = match c {
+            '\n' => NEW_LINE,
+            '\r' => CARRIAGE_RETURN,
+            '{' => {
+                let escape = &string[index..];
+                let end = escape.find('}').map_or(escape.len(), |end| end + 1);
+                let escape = &escape[..end];
+                let code = escape
+                    .strip_prefix("{0x")
+                    .and_then(|code| code.strip_suffix('}'))
+                    .filter(|code| (1..=4).contains(&code.len()))
+                    .and_then(|code| u16::from_str_radix(code, 16).ok())
+                    // control codes are written as `\n`, `\r` or a new string
+                    .filter(|&code| code < NEW_LINE)
+                    .ok_or_else(|| Error::InvalidEscape(escape.into()))?;
+                // skip the rest of the escape
+                chars.nth(escape.chars().count() - 2);
+                code
+            }
+            _ => tables
+                .find_list_character(c)
+                .ok_or(Error::UnmappedCharacter {
+                    location: None,
+                    character: c,
+                })?,
+        };
+        words.push(word);
+    }
+    words.push(TERMINATOR);
+
+    Ok(words)
+}
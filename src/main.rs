@@ -1,24 +1,66 @@
-#![allow(unused_assignments)]
-
-use byteorder::LittleEndian;
-use byteorder::ReadBytesExt;
-use byteorder::WriteBytesExt;
 use clap::Parser;
 use clap::Subcommand;
+use clap::ValueEnum;
 use clap_num::maybe_hex;
+use fe3_text::DialogueCommand;
+use fe3_text::Error;
+use fe3_text::asm::Dialect;
+use fe3_text::bps::apply_bps;
+use fe3_text::bps::create_bps;
+use fe3_text::crc32::crc32;
+use fe3_text::dialogue::decode_dialogue_lenient;
+use fe3_text::encode_dialogue;
+use fe3_text::format_dialogue;
+use fe3_text::ips::create_ips;
+use fe3_text::list::decode_list;
+use fe3_text::list::detect_list_length;
+use fe3_text::list::encode_string;
+use fe3_text::list::list_length;
+use fe3_text::list::terminated_length;
+use fe3_text::opcodes::Byte;
+use fe3_text::parse_dialogue;
+use fe3_text::pointers::PointerTable;
+use fe3_text::pointers::PointerWidth;
+use fe3_text::profiles::Identification;
+use fe3_text::profiles::PointerTableLocation;
+use fe3_text::profiles::Profile;
+use fe3_text::profiles::Profiles;
+use fe3_text::project::MANIFEST;
+use fe3_text::project::Project;
+use fe3_text::rom::Mapping;
+use fe3_text::rom::Position;
+use fe3_text::rom::Rom;
+use fe3_text::scan::ScanOptions;
+use fe3_text::scan::scan_dialogue;
+use fe3_text::space::FreeSpace;
+use fe3_text::tables::Tables;
 use indexmap::IndexMap;
+use std::collections::BTreeMap;
+use std::fs;
 use std::fs::read_to_string;
-use std::fs::File;
-use std::io::Cursor;
-use std::io::Read;
-use std::io::Seek;
-use std::io::SeekFrom;
-use std::io::Write;
+use std::io;
+use std::io::IsTerminal;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
 #[derive(Debug, Parser)]
 struct Args {
     filename: String,
 
+    /// JSON file replacing the default character tables
+    #[arg(long, global = true)]
+    table: Option<String>,
+
+    /// How the ROM is mapped (lorom or hirom), when it isn't detected correctly
+    #[arg(long, global = true)]
+    mapping: Option<Mapping>,
+
+    /// JSON file describing revisions of the game, added to the default ones
+    #[arg(long, global = true)]
+    profiles: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -33,925 +75,1559 @@ enum Commands {
         #[command(subcommand)]
         command: DecompilerCommands,
     },
+    Verify {
+        #[command(subcommand)]
+        command: VerifyCommands,
+    },
+    Scan {
+        #[command(subcommand)]
+        command: ScanCommands,
+    },
+    /// Writes the character tables in use to <FILENAME>, as a starting point for --table
+    DumpTables,
+    /// Shows the cartridge header of the ROM <FILENAME> and the profile it matches
+    Identify,
+    /// Checks the checksum in the cartridge header of the ROM <FILENAME>
+    Checksum {
+        /// Write the correct checksum to <FILENAME>
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Applies a BPS patch to the ROM <FILENAME>, keeping its copier header
+    Apply { patch: String, output: String },
+    /// Compiles every resource of the project manifest <FILENAME> into <OUTPUT>,
+    /// a ROM or an .ips or .bps patch
+    Build { output: String },
+    /// Decompiles every resource of the project manifest <FILENAME> to its source
+    Extract {
+        /// ROM to read instead of the one of the project
+        #[arg(long)]
+        rom: Option<String>,
+        /// Overwrite the sources that already exist
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
 enum CompilerCommands {
-    Dialogue { output: String },
-    List { output: String },
+    Dialogue {
+        output: String,
+        #[command(flatten)]
+        injection: InjectionArgs,
+        /// Write assembler source for asar, ca65, wla-dx, bass or plain instead of binary
+        #[arg(long, conflicts_with = "rom")]
+        asm: Option<Dialect>,
+        /// Label of the dialogue in the assembler source, by default the name of <FILENAME>
+        #[arg(long, requires = "asm")]
+        label: Option<String>,
+    },
+    /// Compiles the <ID>.txt dialogues of the directory <FILENAME> into a copy of a ROM,
+    /// moving the ones that don't fit anymore to free space
+    Dialogues {
+        output: String,
+        /// ROM to write the dialogues into
+        #[arg(long)]
+        rom: String,
+        #[command(flatten)]
+        pointers: PointerTableArgs,
+        #[command(flatten)]
+        space: FreeSpaceArgs,
+    },
+    List {
+        output: String,
+        #[arg(long, value_enum, default_value_t = CompiledListFormat::Asm)]
+        format: CompiledListFormat,
+        /// Syntax of the assembler source: asar, ca65, wla-dx, bass or plain (the default)
+        #[arg(long, conflicts_with = "format")]
+        asm: Option<Dialect>,
+        /// File to write the offset of each label to, one `<offset> <label>` per line
+        #[arg(long)]
+        offsets: Option<String>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
 enum DecompilerCommands {
     Dialogue {
-        #[arg(short, long, value_parser=maybe_hex::<u64>, default_value="0")]
-        offset: u64,
+        #[arg(short, long, default_value = "0")]
+        offset: Position,
+        /// File or directory to write the script to instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Decompiles every dialogue of a pointer table to <OUTPUT>/<ID>.txt, with an index.txt
+    Dialogues {
+        #[command(flatten)]
+        pointers: PointerTableArgs,
+        /// Directory to write the scripts to
+        #[arg(long)]
+        output: PathBuf,
     },
     List {
-        #[arg(short, long, value_parser=maybe_hex::<u64>, default_value="0")]
-        start: u64,
-        #[arg(short, long, value_parser=maybe_hex::<u64>)]
-        end: u64,
+        #[arg(short, long, default_value = "0")]
+        start: Position,
+        /// End of the list. Without it or --count, the list ends before the first
+        /// code that isn't a character
+        #[arg(short, long)]
+        end: Option<Position>,
+        /// Number of strings in the list, instead of --end
+        #[arg(short, long, conflicts_with = "end")]
+        count: Option<usize>,
+        /// Name of the list in the profile of the ROM, instead of --start and --end
+        #[arg(long, conflicts_with_all = ["start", "end", "count"])]
+        name: Option<String>,
+        /// File or directory to write the strings to instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Format of the strings, `json` if --labels is given and `text` otherwise
+        #[arg(long, value_enum)]
+        format: Option<ListFormat>,
+        /// File with one label per line for the JSON output, instead of `string_<index>`
+        #[arg(long)]
+        labels: Option<String>,
     },
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
+/// Writing a compiled dialogue into a ROM instead of a file of its own.
+#[derive(clap::Args, Debug)]
+struct InjectionArgs {
+    /// ROM to write the dialogue into, <OUTPUT> being the modified copy
+    #[arg(long, requires = "at")]
+    rom: Option<String>,
+    /// Position of the dialogue to replace
+    #[arg(long, requires = "rom")]
+    at: Option<Position>,
+    /// Position to write the dialogue to instead, when it's larger than the one it replaces
+    #[arg(long, requires = "pointer")]
+    move_to: Option<Position>,
+    /// Position of the pointer to the dialogue, updated when it moves
+    #[arg(long, requires = "move_to")]
+    pointer: Option<Position>,
+    #[command(flatten)]
+    format: PointerFormatArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct FreeSpaceArgs {
+    /// Region that can be overwritten, as `<start>..<end>`
+    #[arg(long, value_parser=region)]
+    free: Vec<(Position, Position)>,
+    /// Also use the runs of 0xFF or 0x00 bytes of the ROM as free space
+    #[arg(long)]
+    detect_free: bool,
+    /// Shortest run used by --detect-free
+    #[arg(long, value_parser=maybe_hex::<usize>, default_value = "0x40")]
+    min_free: usize,
+}
 
-    match args.command {
-        Commands::Compile { command } => match command {
-            CompilerCommands::Dialogue { output } => compile_dialogue(&args.filename, &output),
-            CompilerCommands::List { output } => compile_array_of_string(&args.filename, &output),
-        },
-        Commands::Decompile { command } => match command {
-            DecompilerCommands::Dialogue { offset } => decompile_dialogue(&args.filename, offset),
-            DecompilerCommands::List { start, end } => {
-                print_array_of_strings(&args.filename, start, end)
-            }
-        },
+fn region(s: &str) -> Result<(Position, Position), String> {
+    let (start, end) = s
+        .split_once("..")
+        .ok_or_else(|| format!("`{s}` is not a region like `0x1000..0x2000`"))?;
+    Ok((start.parse()?, end.parse()?))
+}
+
+impl FreeSpaceArgs {
+    /// The free space of the ROM, except for `pointers` and the cartridge header.
+    fn resolve(
+        &self,
+        rom: &Rom,
+        pointers: &[PointerTable],
+    ) -> Result<FreeSpace, Box<dyn std::error::Error>> {
+        let mut space = if self.detect_free {
+            FreeSpace::detect(&rom.data, self.min_free)
+        } else {
+            FreeSpace::default()
+        };
+        for &(start, end) in &self.free {
+            space.free(resolve(rom, start)?..resolve(rom, end)?);
+        }
+        space.reserve(rom.header_range());
+        for pointers in pointers {
+            space.reserve(pointers.offset..pointers.end());
+        }
+        Ok(space)
     }
 }
 
-fn decompile_dialogue(filename: &str, offset: u64) -> Result<(), Box<dyn std::error::Error>> {
-    let mut rom = File::open(filename)?;
-    rom.seek(SeekFrom::Start(offset))?;
+#[derive(clap::Args, Debug)]
+struct PointerTableArgs {
+    /// Name of the pointer table in the profile of the ROM
+    #[arg(long, conflicts_with_all = ["pointers", "count", "width", "bank"])]
+    name: Option<String>,
+    /// Position of the pointer table
+    #[arg(long, required_unless_present = "name")]
+    pointers: Option<Position>,
+    /// Number of pointers in the table
+    #[arg(long, required_unless_present = "name")]
+    count: Option<usize>,
+    #[command(flatten)]
+    format: PointerFormatArgs,
+}
 
-    let mut missing = false;
-    let mut page = 0;
-    loop {
-        let id = rom.read_u8()?;
+#[derive(clap::Args, Debug)]
+struct PointerFormatArgs {
+    /// Size of the pointers in bits
+    #[arg(long, value_enum, default_value = "16")]
+    width: PointerBits,
+    /// Bank of 16-bit pointers, by default the bank they are in
+    #[arg(long, value_parser=maybe_hex::<u8>)]
+    bank: Option<u8>,
+}
 
-        if id == 0 {
-            let command = rom.read_u8()?;
-            match command {
-                0 => {
-                    println!("[End]");
-                    break;
-                }
-                0x01 => print!("\\n"),
-                0x02 => println!("[ClearFrame]"),
-                0x05 => println!("[Unknown05]"),
-                0x07 => {
-                    let zero = rom.read_u8()?;
-                    assert_eq!(zero, 0);
-                    let colour = rom.read_u8()?;
-                    let colour = match colour {
-                        0x20 => "Brown",
-                        0x24 => "White",
-                        0x28 => "Yellow",
-                        0x2C => "Green",
-                        _ => panic!("Unknown colour: {colour:#02X}"),
-                    };
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum PointerBits {
+    #[value(name = "16")]
+    Short,
+    #[value(name = "24")]
+    Long,
+}
 
-                    print!("[SetColor({colour})]");
-                }
-                0x0C => {
-                    let other = rom.read_u8()?;
-                    println!("[Unknown0C({other})]");
-                }
-                0x10 => {
-                    let unk1 = rom.read_u8()?;
-                    let unk2 = rom.read_u8()?;
-                    let unk3 = rom.read_u8()?;
-                    println!("[Unknown10({unk1}, {unk2}, {unk3})]");
-                }
-                0x11 | 0x12 | 0x13 | 0x14 => page = command as usize - 0x11,
-                0x16 => {
-                    let ten = rom.read_u8()?;
-                    assert_eq!(ten, 0x10);
-                    let zero = rom.read_u8()?;
-                    assert_eq!(zero, 0);
-
-                    println!("[Unknown16]");
-                }
-                0x17 => {
-                    let ef = rom.read_u8()?;
-                    assert_eq!(ef, 0xEF);
-                    let ff = rom.read_u8()?;
-                    assert_eq!(ff, 0xFF);
+impl PointerFormatArgs {
+    /// The width of pointers stored at `offset`.
+    fn resolve(
+        &self,
+        rom: &Rom,
+        offset: usize,
+    ) -> Result<PointerWidth, Box<dyn std::error::Error>> {
+        Ok(match self.width {
+            PointerBits::Short => PointerWidth::Short {
+                bank: match self.bank {
+                    Some(bank) => bank,
+                    None => rom
+                        .address(offset)
+                        .ok_or_else(|| format!("pointer {offset:#X} is outside of the ROM"))?
+                        .bank(),
+                },
+            },
+            PointerBits::Long => PointerWidth::Long,
+        })
+    }
+}
 
-                    println!("[Unknown17]");
-                }
-                0x80 => println!("[Unknown80]"),
-                0x81 => println!("[Unknown81]"),
-                0x82 => println!("[Unknown82]"),
-                0x84 => {
-                    let portrait = rom.read_u8()?;
-                    let flags = rom.read_u8()?;
-                    let portrait = PORTRAITS[portrait as usize];
-
-                    if portrait.starts_with("Unknown") {
-                        print!("\x1b[93m");
-                        missing = true;
-                    }
+impl PointerTableArgs {
+    fn resolve(
+        &self,
+        rom: &Rom,
+        profiles: &Profiles,
+    ) -> Result<PointerTable, Box<dyn std::error::Error>> {
+        let location = match &self.name {
+            Some(name) => profile(rom, profiles, name)?
+                .dialogues
+                .get(name)
+                .ok_or_else(|| format!("the profile of the ROM has no dialogues `{name}`"))?
+                .clone(),
+            None => PointerTableLocation {
+                pointers: self.pointers.unwrap(),
+                count: self.count.unwrap(),
+                width: match self.format.width {
+                    PointerBits::Short => 16,
+                    PointerBits::Long => 24,
+                },
+                bank: self.format.bank.map(Byte),
+            },
+        };
+        pointer_table(rom, &location)
+    }
+}
 
-                    let position = match flags & 0b11 {
-                        0b00 => "TopLeft",
-                        0b01 => "TopRight",
-                        0b10 => "BottomLeft",
-                        0b11 => "BottomRight",
-                        _ => unreachable!(),
-                    };
-                    let flags = flags >> 2;
+/// The pointer table at `location`, checking that it fits in the ROM.
+fn pointer_table(
+    rom: &Rom,
+    location: &PointerTableLocation,
+) -> Result<PointerTable, Box<dyn std::error::Error>> {
+    let format = PointerFormatArgs {
+        width: match location.width {
+            24 => PointerBits::Long,
+            _ => PointerBits::Short,
+        },
+        bank: location.bank.map(|bank| bank.0),
+    };
+    let offset = resolve(rom, location.pointers)?;
+    let table = PointerTable {
+        offset,
+        count: location.count,
+        width: format.resolve(rom, offset)?,
+    };
+    if table.end() > rom.data.len() {
+        return Err(format!(
+            "the pointer table at {} goes past the end of the ROM",
+            describe(rom, offset)
+        )
+        .into());
+    }
 
-                    println!("[ShowPortrait({portrait}, {position}, {flags:#02X})]");
+    Ok(table)
+}
 
-                    if portrait.starts_with("Unknown") {
-                        print!("\x1b[0m");
-                    }
-                }
-                0x85 => {
-                    let flags = rom.read_u8()?;
-
-                    let position = match flags & 0b11 {
-                        0b00 => "TopLeft",
-                        0b01 => "TopRight",
-                        0b10 => "BottomLeft",
-                        0b11 => "BottomRight",
-                        _ => unreachable!(),
-                    };
-                    let flags = flags >> 2;
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum ListFormat {
+    /// One quoted string per line
+    Text,
+    /// A label → string object, as read by `compile list`
+    Json,
+}
 
-                    println!("[CloseFrame({position}, {flags:#02X})]");
-                }
-                0x86 => println!("[Unknown86]"),
-                0x87 => println!("[Unknown87]"),
-                0x88 => {
-                    let kind = rom.read_u8()?;
-                    let kind = match kind {
-                        0x00 => "Default",
-                        0x03 => "Ending",
-                        0x04 => "Village",
-                        0x06 => "Shop",
-                        _ => panic!("Unknown dialogue kind: {kind:02X}"),
-                    };
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum CompiledListFormat {
+    /// A label and a `.db` line per string
+    Asm,
+    /// The strings as they are stored in the ROM
+    Binary,
+}
 
-                    println!("[StartDialogue({kind})]");
-                }
-                0x89 => {
-                    let song = rom.read_u8()?;
-                    let volume = rom.read_u8()?;
+#[derive(Subcommand, Debug)]
+enum VerifyCommands {
+    Dialogue {
+        #[arg(short, long, default_value = "0")]
+        offset: Position,
+    },
+}
 
-                    println!("[PlaySong({song}, {volume})]");
-                }
-                0x8A => println!("[WaitForA]"),
-                0x8B => println!("[Unknown8B]"),
-                0x8C => println!("[Unknown8C]"),
-                0x8D => {
-                    let unk1 = rom.read_u8()?;
-                    let unk2 = rom.read_u8()?;
-
-                    println!("[Unknown8D({unk1}, {unk2})]");
-                }
-                0x8E => {
-                    let unk1 = rom.read_u8()?;
-                    let unk2 = rom.read_u8()?;
+#[derive(Subcommand, Debug)]
+enum ScanCommands {
+    /// Lists the regions that decode as dialogue scripts, with a score out of 100
+    Dialogue {
+        #[arg(short, long, default_value = "0")]
+        start: Position,
+        #[arg(short, long)]
+        end: Option<Position>,
+        #[arg(long, default_value_t = ScanOptions::default().min_score)]
+        min_score: u8,
+        #[arg(long, value_parser=maybe_hex::<usize>, default_value_t = ScanOptions::default().min_length)]
+        min_length: usize,
+        #[arg(long, value_parser=maybe_hex::<usize>, default_value_t = ScanOptions::default().max_length)]
+        max_length: usize,
+    },
+}
 
-                    println!("[Unknown8E({unk1}, {unk2})]");
-                }
-                0x8F => {
-                    let speed = rom.read_u8()?;
-                    let speed = match speed {
-                        0x84 => "Fast",
-                        0x89 => "Slow",
-                        _ => panic!("Unknown speed: {speed:#02X}"),
-                    };
-                    println!("[ChangeTextSpeed({speed})]");
-                }
-                0x90 => println!("[Unknown90]"),
-                0x91 => println!("[Unknown91]"),
-                0x92 => {
-                    let position = rom.read_u8()?;
-                    let position = match position {
-                        0x00 => "Top",
-                        0x01 => "Bottom",
-                        _ => panic!("Unknown position: {position:#02X}"),
-                    };
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
 
-                    println!("[SwitchFrame({position})]");
-                }
-                0x93 => println!("[Unknown93]"),
-                0x94 => {
-                    let frames = rom.read_u16::<LittleEndian>()?;
-                    println!("[TimedWaitForA({frames})]");
+fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let tables = match &args.table {
+        Some(table) => Tables::load(table).map_err(|error| format!("{table}: {error}"))?,
+        None => Tables::default(),
+    };
+    let profiles = match &args.profiles {
+        Some(profiles) => {
+            Profiles::load(profiles).map_err(|error| format!("{profiles}: {error}"))?
+        }
+        None => Profiles::default(),
+    };
+
+    match args.command {
+        Commands::Compile { command } => match command {
+            CompilerCommands::Dialogue {
+                output,
+                injection,
+                asm,
+                label,
+            } => {
+                let script = compile_dialogue(&args.filename, &tables)?;
+                match (&injection.rom, asm) {
+                    (Some(rom), _) => {
+                        let rom = load_rom(rom, args.mapping, &profiles)?;
+                        inject_dialogue(&rom, &script, &injection, &output, &tables)
+                    }
+                    (None, Some(dialect)) => {
+                        let label = label.unwrap_or_else(|| default_label(&args.filename));
+                        let source = format!("{label}:\n{}", dialect.bytes(&script));
+                        Ok(fs::write(&output, source)?)
+                    }
+                    (None, None) => Ok(fs::write(&output, script)?),
                 }
-                0x95 => println!("[Unknown95]"),
-                _ => panic!(
-                    "Unknown command {command:#02X} at index {:#X}",
-                    rom.seek(SeekFrom::Current(0))?
-                ),
             }
-        } else {
-            let mut character = id;
-            while character != 0 {
-                let c = DIALOGUES_CHARACTERS[page][character as usize];
-                if c == '_' {
-                    missing = true;
-                    print!(" \x1b[93m{:02X}/{:02X}\x1b[0m ", page + 0x11, character);
-                } else {
-                    print!("{c}");
-                }
-                character = rom.read_u8()?;
+            CompilerCommands::Dialogues {
+                output,
+                rom,
+                pointers,
+                space,
+            } => {
+                let rom = load_rom(&rom, args.mapping, &profiles)?;
+                let pointers = pointers.resolve(&rom, &profiles)?;
+                let mut space = space.resolve(&rom, &[pointers])?;
+                let mut modified = rom.clone();
+                compile_dialogues(
+                    &args.filename,
+                    &mut modified,
+                    &pointers,
+                    &mut space,
+                    &tables,
+                )?;
+                write_rom(&output, &rom, &mut modified)
+            }
+            CompilerCommands::List {
+                output,
+                format,
+                asm,
+                offsets,
+            } => compile_array_of_string(
+                &args.filename,
+                &output,
+                format,
+                asm,
+                offsets.as_deref(),
+                &tables,
+            ),
+        },
+        Commands::Decompile { command } => match command {
+            DecompilerCommands::Dialogue { offset, output } => {
+                let rom = load_rom(&args.filename, args.mapping, &profiles)?;
+                decompile_dialogue(&rom, offset, output.as_deref(), &tables)
+            }
+            DecompilerCommands::Dialogues { pointers, output } => {
+                let rom = load_rom(&args.filename, args.mapping, &profiles)?;
+                let pointers = pointers.resolve(&rom, &profiles)?;
+                decompile_dialogues(&rom, &pointers, &output, &tables)
+            }
+            DecompilerCommands::List {
+                start,
+                end,
+                count,
+                name,
+                output,
+                format,
+                labels,
+            } => {
+                let format = match (format, &labels) {
+                    (Some(ListFormat::Text), Some(_)) => {
+                        return Err("--labels can't be used with --format text".into());
+                    }
+                    (Some(format), _) => format,
+                    (None, Some(_)) => ListFormat::Json,
+                    (None, None) => ListFormat::Text,
+                };
+                let list = ListOptions {
+                    output: output.as_deref(),
+                    format,
+                    labels: labels.as_deref(),
+                };
+                let rom = load_rom(&args.filename, args.mapping, &profiles)?;
+                let (start, end) =
+                    match &name {
+                        Some(name) => {
+                            let location =
+                                profile(&rom, &profiles, name)?.lists.get(name).ok_or_else(
+                                    || format!("the profile of the ROM has no list `{name}`"),
+                                )?;
+                            (location.start, ListEnd::At(location.end))
+                        }
+                        None => match (end, count) {
+                            (Some(end), _) => (start, ListEnd::At(end)),
+                            (None, Some(count)) => (start, ListEnd::Count(count)),
+                            (None, None) => (start, ListEnd::Detect),
+                        },
+                    };
+                print_array_of_strings(&rom, start, end, &list, &tables)
+            }
+        },
+        Commands::Verify { command } => match command {
+            VerifyCommands::Dialogue { offset } => {
+                let rom = load_rom(&args.filename, args.mapping, &profiles)?;
+                verify_dialogue(&rom, offset, &tables)
             }
+        },
+        Commands::Scan { command } => match command {
+            ScanCommands::Dialogue {
+                start,
+                end,
+                min_score,
+                min_length,
+                max_length,
+            } => {
+                let rom = load_rom(&args.filename, args.mapping, &profiles)?;
+                let options = ScanOptions {
+                    min_length,
+                    max_length,
+                    min_score,
+                };
+                scan_dialogues(&rom, start, end, &options, &tables)
+            }
+        },
+        Commands::Identify => {
+            let rom = load_rom(&args.filename, args.mapping, &profiles)?;
+            identify(&rom, &profiles);
+            Ok(())
+        }
+        Commands::Checksum { fix } => {
+            let mut rom = load_rom(&args.filename, args.mapping, &profiles)?;
+            check_checksum(&args.filename, &mut rom, fix)
+        }
+        Commands::Apply { patch, output } => {
+            let file =
+                fs::read(&args.filename).map_err(|error| format!("{}: {error}", args.filename))?;
+            let mut rom = Rom::new(file, args.mapping);
+            let patch_data = fs::read(&patch).map_err(|error| format!("{patch}: {error}"))?;
+            // the patch is made without the copier header, which is kept as it is
+            rom.data =
+                apply_bps(&rom.data, &patch_data).map_err(|error| format!("{patch}: {error}"))?;
+            fs::write(&output, rom.to_file()).map_err(|error| format!("{output}: {error}"))?;
+            Ok(())
+        }
+        Commands::Build { output } => {
+            build(&args.filename, &output, args.mapping, &profiles, &tables)
+        }
+        Commands::Extract { rom, force } => extract(
+            &args.filename,
+            rom.as_deref(),
+            force,
+            args.mapping,
+            &profiles,
+            &tables,
+        ),
+        Commands::DumpTables => Ok(fs::write(&args.filename, tables.to_json())?),
+    }
+}
+
+/// Reads a ROM, warning if it doesn't match any of `profiles`.
+fn load_rom(
+    filename: &str,
+    mapping: Option<Mapping>,
+    profiles: &Profiles,
+) -> Result<Rom, Box<dyn std::error::Error>> {
+    let file = fs::read(filename).map_err(|error| format!("{filename}: {error}"))?;
+    let rom = Rom::new(file, mapping);
+
+    match profiles.identify(&rom) {
+        Identification::Header(name, profile) if profile.crc32.is_some() => eprintln!(
+            "warning: {filename} has the header of {name} but not its CRC32, it may have been modified"
+        ),
+        Identification::Unknown if !profiles.is_empty() => eprintln!(
+            "warning: {filename} matches no profile (CRC32 {:08X}, title {:?}, version {})",
+            crc32(&rom.data),
+            rom.title().unwrap_or_default(),
+            rom.version().unwrap_or_default()
+        ),
+        _ => {}
+    }
+
+    Ok(rom)
+}
+
+/// The profile of `rom`, to find the location called `name`.
+fn profile<'a>(
+    rom: &Rom,
+    profiles: &'a Profiles,
+    name: &str,
+) -> Result<&'a Profile, Box<dyn std::error::Error>> {
+    match profiles.identify(rom).profile() {
+        Some((_, profile)) => Ok(profile),
+        None => Err(format!("no profile matches the ROM, so `{name}` is unknown").into()),
+    }
+}
 
-            rom.seek(SeekFrom::Current(-1))?;
+/// The offset in the ROM of `position`.
+fn resolve(rom: &Rom, position: Position) -> Result<usize, Box<dyn std::error::Error>> {
+    rom.resolve(position).ok_or_else(|| match position {
+        Position::Offset(offset) => format!("offset {offset:#X} is outside of the ROM").into(),
+        Position::Address(address) => {
+            format!("{address} is not mapped to the ROM in {:?}", rom.mapping).into()
         }
+    })
+}
+
+/// `offset` followed by its SNES address.
+fn describe(rom: &Rom, offset: usize) -> String {
+    match rom.address(offset) {
+        Some(address) => format!("{offset:#X} ({address})"),
+        None => format!("{offset:#X}"),
     }
+}
 
-    if missing {
-        todo!();
+/// Adds the SNES address of a decoding error to its message.
+fn located(rom: &Rom, error: Error) -> Box<dyn std::error::Error> {
+    match error.offset().and_then(|offset| rom.address(offset)) {
+        Some(address) => format!("{error} (at {address})").into(),
+        None => error.into(),
     }
+}
+
+/// Writes decompiled text to `output`, or to stdout when there is none. A
+/// directory gets a file named `default_name` inside it.
+fn write_output(
+    output: Option<&Path>,
+    default_name: &str,
+    text: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(output) = output else {
+        print!("{text}");
+        return Ok(());
+    };
+
+    let path = if output.is_dir() {
+        output.join(default_name)
+    } else {
+        output.to_path_buf()
+    };
+    fs::write(&path, text).map_err(|error| format!("{}: {error}", path.display()))?;
 
     Ok(())
 }
 
-// rewrite this ugly code
-fn compile_dialogue(filename: &str, output: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let script = read_to_string(filename)?;
-    let mut file = File::create(output)?;
-    let script = script.chars().collect::<Vec<_>>();
-    let mut index = 0;
-    let mut current_page = 0;
-    while index < script.len() {
-        if script[index] == '[' {
-            file.write_u8(0)?;
-
-            let mut end_index = index + 1;
-            while end_index < script.len() && script[end_index] != ']' && script[end_index] != '(' {
-                end_index += 1;
+fn decompile_dialogue(
+    rom: &Rom,
+    offset: Position,
+    output: Option<&Path>,
+    tables: &Tables,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let offset = resolve(rom, offset)?;
+    let commands = decode_dialogue_lenient(&rom.data[offset..], tables)
+        .map_err(|error| located(rom, error.rebase(offset)))?
+        .into_iter()
+        .map(|(_, command)| command)
+        .collect::<Vec<_>>();
+
+    let highlight = output.is_none() && io::stdout().is_terminal();
+    write_output(
+        output,
+        &format!("dialogue_{offset:06X}.txt"),
+        &format_dialogue(&commands, tables, highlight),
+    )?;
+
+    let glyphs = commands
+        .iter()
+        .filter(|command| matches!(command, DialogueCommand::UnknownGlyph { .. }))
+        .count();
+    let raw = commands
+        .iter()
+        .filter(|command| matches!(command, DialogueCommand::Raw(_)))
+        .count();
+    if glyphs > 0 {
+        eprintln!("warning: {glyphs} unidentified glyph(s), written as `{{page:code}}`");
+    }
+    if raw > 0 {
+        eprintln!("warning: {raw} run(s) of undecoded bytes, written as `[Raw(...)]`");
+    }
+
+    Ok(())
+}
+
+fn decompile_dialogues(
+    rom: &Rom,
+    pointers: &PointerTable,
+    output: &Path,
+    tables: &Tables,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(output).map_err(|error| format!("{}: {error}", output.display()))?;
+    let digits = pointers.count.saturating_sub(1).to_string().len();
+    let column = digits.max(2);
+    let mut index = format!(
+        "{:<column$}  {:<18}  {:<18}  LENGTH\n",
+        "ID", "POINTER", "TARGET"
+    );
+    let mut failures = 0;
+
+    for id in 0..pointers.count {
+        let pointer = describe(rom, pointers.entry(id));
+        let address = pointers.read(&rom.data, id).unwrap();
+        let target = rom
+            .offset(address)
+            .map_or(address.to_string(), |offset| describe(rom, offset));
+        let decompiled = rom
+            .offset(address)
+            .ok_or_else(|| format!("{address} is not mapped to the ROM"))
+            .and_then(|offset| {
+                let spans = decode_dialogue_lenient(&rom.data[offset..], tables)
+                    .map_err(|error| located(rom, error.rebase(offset)).to_string())?;
+                Ok(spans)
+            });
+
+        let spans = match decompiled {
+            Ok(decompiled) => decompiled,
+            Err(error) => {
+                eprintln!("error: dialogue {id}: {error}");
+                index += &format!("{id:<column$}  {pointer:<18}  {target:<18}  error: {error}\n");
+                failures += 1;
+                continue;
             }
+        };
+        let length = spans.last().map_or(0, |(span, _)| span.end);
+        let commands = spans
+            .into_iter()
+            .map(|(_, command)| command)
+            .collect::<Vec<_>>();
+
+        let path = output.join(format!("{id:0digits$}.txt"));
+        fs::write(&path, format_dialogue(&commands, tables, false))
+            .map_err(|error| format!("{}: {error}", path.display()))?;
+        index += &format!("{id:<column$}  {pointer:<18}  {target:<18}  {length}\n");
+    }
 
-            let command = String::from_iter(&script[(index + 1)..end_index]);
-            let mut args = vec![];
+    let path = output.join("index.txt");
+    fs::write(&path, index).map_err(|error| format!("{}: {error}", path.display()))?;
 
-            index = end_index;
-            if script[index] == '(' {
-                index += 1;
-                end_index = index;
+    if failures > 0 {
+        return Err(format!(
+            "{failures} of {} dialogue(s) could not be decompiled",
+            pointers.count
+        )
+        .into());
+    }
 
-                let mut depth = 0;
-                while end_index < script.len() && (script[end_index] != ')' || depth > 0) {
-                    if script[end_index] == '(' {
-                        depth += 1;
-                    }
-                    if script[end_index] == ')' {
-                        depth -= 1;
-                    }
-                    end_index += 1;
-                }
+    Ok(())
+}
 
-                args = String::from_iter(&script[index..end_index])
-                    .split(",")
-                    .map(str::trim)
-                    .map(String::from)
-                    .collect::<Vec<_>>();
+fn scan_dialogues(
+    rom: &Rom,
+    start: Position,
+    end: Option<Position>,
+    options: &ScanOptions,
+    tables: &Tables,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let start = resolve(rom, start)?;
+    let end = match end {
+        Some(end) => resolve(rom, end)?.max(start),
+        None => rom.data.len(),
+    };
+
+    let candidates = scan_dialogue(&rom.data[start..end], tables, options);
+    for candidate in &candidates {
+        println!(
+            "{}: {} bytes, score {}",
+            describe(rom, start + candidate.range.start),
+            candidate.range.len(),
+            candidate.score
+        );
+    }
+    println!("{} candidate(s)", candidates.len());
 
-                assert_eq!(script[end_index], ')');
-                index = end_index + 1;
+    Ok(())
+}
+
+fn compile_dialogue(
+    filename: &str,
+    tables: &Tables,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let script = read_to_string(filename)?;
+    let commands = match parse_dialogue(&script, tables) {
+        Ok(commands) => commands,
+        Err(errors) => {
+            for error in &errors {
+                eprintln!("{}\n", error.diagnostic(filename, &script));
             }
-            if script[index] != ']' {
-                panic!("ERROR // {} / {}", index, script[index]);
+            return Err(format!(
+                "could not compile {filename} due to {} error(s)",
+                errors.len()
+            )
+            .into());
+        }
+    };
+
+    Ok(encode_dialogue(&commands, tables)?)
+}
+
+/// A label made from the name of the file `filename`, like `dialogue_12` for
+/// `12.txt`.
+fn default_label(filename: &str) -> String {
+    let stem = Path::new(filename)
+        .file_stem()
+        .map_or("dialogue".into(), |stem| stem.to_string_lossy());
+    let label = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if label.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("dialogue_{label}")
+    } else {
+        label
+    }
+}
+
+/// Replaces the dialogue at `injection.at` with `script`, or writes it at
+/// `injection.move_to` and updates its pointer, and saves the ROM to `output`.
+fn inject_dialogue(
+    original: &Rom,
+    script: &[u8],
+    injection: &InjectionArgs,
+    output: &str,
+    tables: &Tables,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut rom = original.clone();
+    let at = resolve(&rom, injection.at.unwrap())?;
+    let slot = decode_dialogue_lenient(&rom.data[at..], tables)
+        .map_err(|error| located(&rom, error.rebase(at)))?
+        .last()
+        .map_or(0, |(span, _)| span.end);
+
+    let destination = match (injection.move_to, injection.pointer) {
+        (Some(move_to), Some(pointer)) => {
+            let destination = resolve(&rom, move_to)?;
+            let pointer = resolve(&rom, pointer)?;
+            let table = PointerTable {
+                offset: pointer,
+                count: 1,
+                width: injection.format.resolve(&rom, pointer)?,
+            };
+            let current = table
+                .read(&rom.data, 0)
+                .and_then(|address| rom.offset(address));
+            if current != Some(at) {
+                return Err(format!(
+                    "the pointer at {} doesn't point to the dialogue at {}",
+                    describe(&rom, pointer),
+                    describe(&rom, at)
+                )
+                .into());
             }
+            table
+                .write(&mut rom, 0, destination)
+                .map_err(|error| located(&rom, error))?;
+            println!(
+                "{}: now points to {}",
+                describe(&rom, pointer),
+                describe(&rom, destination)
+            );
+            destination
+        }
+        _ if script.len() > slot => {
+            return Err(format!(
+                "the dialogue is {} bytes but the one at {} is only {slot}, use --move-to and --pointer to write it elsewhere",
+                script.len(),
+                describe(&rom, at)
+            )
+            .into());
+        }
+        _ => at,
+    };
+
+    rom.data
+        .get_mut(destination..destination + script.len())
+        .ok_or_else(|| format!("the dialogue doesn't fit at {destination:#X}"))?
+        .copy_from_slice(script);
+    if destination == at {
+        println!(
+            "{}: wrote {} bytes over {slot}",
+            describe(&rom, destination),
+            script.len()
+        );
+    } else {
+        println!(
+            "{}: wrote {} bytes",
+            describe(&rom, destination),
+            script.len()
+        );
+    }
 
-            match command.as_str() {
-                "End" => file.write_u8(0x00)?,
-                "ClearFrame" => file.write_u8(0x02)?,
-                "Unknown05" => file.write_u8(0x05)?,
-                "SetColor" => {
-                    assert_eq!(args.len(), 1);
-
-                    file.write_u8(0x07)?;
-                    file.write_u8(0x00)?;
-
-                    let colour = match args[0].as_str() {
-                        "Brown" => 0x20,
-                        "White" => 0x24,
-                        "Yellow" => 0x28,
-                        "Green" => 0x2C,
-                        _ => panic!("Invalid colour {}", args[0]),
-                    };
-                    file.write_u8(colour)?;
-                }
-                "Unknown0C" => {
-                    assert_eq!(args.len(), 1);
+    write_rom(output, original, &mut rom)
+}
 
-                    file.write_u8(0x0C)?;
+/// Where a dialogue went in [`compile_dialogues`].
+struct Placement {
+    id: usize,
+    range: Range<usize>,
+    /// Where the dialogue was when it moved.
+    moved_from: Option<usize>,
+}
 
-                    let unk = maybe_hex::<u8>(&args[0]).unwrap();
-                    file.write_u8(unk)?;
-                }
-                "Unknown10" => {
-                    assert_eq!(args.len(), 3);
+/// Writes the `<ID>.txt` dialogues of `directory` into `rom`, moving the ones
+/// that don't fit anymore to `space`.
+fn compile_dialogues(
+    directory: &str,
+    rom: &mut Rom,
+    pointers: &PointerTable,
+    space: &mut FreeSpace,
+    tables: &Tables,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut scripts = BTreeMap::new();
+    let entries = fs::read_dir(directory).map_err(|error| format!("{directory}: {error}"))?;
+    for entry in entries {
+        let path = entry?.path();
+        let id = path
+            .file_stem()
+            .and_then(|stem| stem.to_str()?.parse::<usize>().ok())
+            .filter(|&id| id < pointers.count);
+        if let Some(id) = id
+            && path.extension().is_some_and(|extension| extension == "txt")
+        {
+            scripts.insert(id, path);
+        }
+    }
 
-                    file.write_u8(0x10)?;
+    let targets = (0..pointers.count)
+        .map(|id| {
+            let address = pointers.read(&rom.data, id).unwrap();
+            rom.offset(address)
+        })
+        .collect::<Vec<_>>();
+    // pointers to each dialogue, which can only be freed when none is left
+    let mut references = BTreeMap::<usize, usize>::new();
+    for &target in targets.iter().flatten() {
+        *references.entry(target).or_default() += 1;
+    }
 
-                    let unk1 = maybe_hex::<u8>(&args[0]).unwrap();
-                    let unk2 = maybe_hex::<u8>(&args[1]).unwrap();
-                    let unk3 = maybe_hex::<u8>(&args[2]).unwrap();
-                    file.write_u8(unk1)?;
-                    file.write_u8(unk2)?;
-                    file.write_u8(unk3)?;
-                }
-                "Unknown16" => {
-                    file.write_u8(0x16)?;
+    let mut failures = 0;
+    let mut placements = vec![];
+    let mut moves = vec![];
+    for (&id, path) in &scripts {
+        let filename = path.display().to_string();
+        // a script without `[End]` fails here, before its slot is overwritten or freed
+        let script = match compile_dialogue(&filename, tables) {
+            Ok(script) => script,
+            Err(error) => {
+                eprintln!("error: {error}");
+                failures += 1;
+                continue;
+            }
+        };
+        let target = targets[id]
+            .ok_or_else(|| format!("dialogue {id}: its pointer is not mapped to the ROM"))?;
+        let slot = decode_dialogue_lenient(&rom.data[target..], tables)
+            .map_err(|error| format!("dialogue {id}: {}", located(rom, error.rebase(target))))?
+            .last()
+            .map_or(0, |(span, _)| span.end);
+
+        if rom.data[target..target + slot] == script {
+            continue;
+        }
+        if references[&target] == 1 && script.len() <= slot {
+            rom.data[target..target + script.len()].copy_from_slice(&script);
+            space.free(target + script.len()..target + slot);
+            placements.push(Placement {
+                id,
+                range: target..target + script.len(),
+                moved_from: None,
+            });
+        } else {
+            let references = references.get_mut(&target).unwrap();
+            *references -= 1;
+            if *references == 0 {
+                space.free(target..target + slot);
+            }
+            moves.push((id, target, script));
+        }
+    }
+    if failures > 0 {
+        return Err(format!("could not compile {failures} dialogue(s)").into());
+    }
 
-                    file.write_u8(0x10)?;
-                    file.write_u8(0x00)?;
-                }
-                "Unknown17" => {
-                    file.write_u8(0x17)?;
+    for (id, target, script) in moves {
+        let within = match pointers.width {
+            PointerWidth::Short { bank } => rom
+                .bank_range(bank)
+                .ok_or_else(|| format!("bank ${bank:02X} is not mapped to the ROM"))?,
+            PointerWidth::Long => 0..rom.data.len(),
+        };
+        let destination = space
+            .allocate(script.len(), within.clone(), rom.bank_size())
+            .ok_or_else(|| {
+                format!(
+                    "no free space for the {} bytes of dialogue {id} between {} and {}",
+                    script.len(),
+                    describe(rom, within.start),
+                    describe(rom, within.end - 1)
+                )
+            })?;
+        rom.data[destination..destination + script.len()].copy_from_slice(&script);
+        pointers
+            .write(rom, id, destination)
+            .map_err(|error| located(rom, error))?;
+        placements.push(Placement {
+            id,
+            range: destination..destination + script.len(),
+            moved_from: Some(target),
+        });
+    }
 
-                    file.write_u8(0xEF)?;
-                    file.write_u8(0xFF)?;
-                }
-                "Unknown80" => file.write_u8(0x80)?,
-                "Unknown81" => file.write_u8(0x81)?,
-                "Unknown82" => file.write_u8(0x82)?,
-                "ShowPortrait" => {
-                    assert_eq!(args.len(), 3);
-
-                    file.write_u8(0x84)?;
-
-                    let portrait_id = PORTRAITS.iter().position(|c| c == &args[0]).unwrap();
-                    let position = &args[1];
-                    let flags = maybe_hex::<u8>(&args[2]).unwrap() << 2;
-                    let flags = flags
-                        + match position.as_str() {
-                            "TopLeft" => 0b00,
-                            "TopRight" => 0b01,
-                            "BottomLeft" => 0b10,
-                            "BottomRight" => 0b11,
-                            _ => panic!("Invalid position {position}"),
-                        };
-
-                    file.write_u8(portrait_id as u8)?;
-                    file.write_u8(flags)?;
-                }
-                "CloseFrame" => {
-                    assert_eq!(args.len(), 2);
-
-                    file.write_u8(0x85)?;
-
-                    let position = &args[0];
-                    let flags = maybe_hex::<u8>(&args[1]).unwrap() << 2;
-                    let flags = flags
-                        + match position.as_str() {
-                            "TopLeft" => 0b00,
-                            "TopRight" => 0b01,
-                            "BottomLeft" => 0b10,
-                            "BottomRight" => 0b11,
-                            _ => panic!("Invalid position {position}"),
-                        };
-
-                    file.write_u8(flags)?;
-                }
-                "Unknown86" => file.write_u8(0x86)?,
-                "Unknown87" => file.write_u8(0x87)?,
-                "StartDialogue" => {
-                    assert_eq!(args.len(), 1);
-
-                    file.write_u8(0x88)?;
-
-                    let kind = &args[0];
-                    let kind = match kind.as_str() {
-                        "Default" => 0x00,
-                        "Ending" => 0x03,
-                        "Village" => 0x04,
-                        "Shop" => 0x06,
-                        _ => panic!("Unknown dialogue kind: {kind}"),
-                    };
+    let mut map = placements
+        .iter()
+        .map(|placement| {
+            let moved = placement
+                .moved_from
+                .map(|from| format!(", moved from {}", describe(rom, from)))
+                .unwrap_or_default();
+            let line = format!(
+                "{}: dialogue {}, {} bytes{moved}",
+                describe(rom, placement.range.start),
+                placement.id,
+                placement.range.len()
+            );
+            (placement.range.start, line)
+        })
+        .chain(space.regions().iter().map(|region| {
+            let line = format!(
+                "{}: free, {} bytes",
+                describe(rom, region.start),
+                region.len()
+            );
+            (region.start, line)
+        }))
+        .collect::<Vec<_>>();
+    map.sort();
+    for (_, line) in map {
+        println!("{line}");
+    }
+    println!(
+        "{} dialogue(s) written, {} moved, {} bytes left free",
+        placements.len(),
+        placements
+            .iter()
+            .filter(|placement| placement.moved_from.is_some())
+            .count(),
+        space.total()
+    );
 
-                    file.write_u8(kind)?;
-                }
-                "PlaySong" => {
-                    assert_eq!(args.len(), 2);
+    Ok(())
+}
 
-                    file.write_u8(0x89)?;
+/// Compiles every resource of the manifest `filename` into a copy of its ROM,
+/// and writes it to `output` only if none of them failed.
+fn build(
+    filename: &str,
+    output: &str,
+    mapping: Option<Mapping>,
+    profiles: &Profiles,
+    tables: &Tables,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let project = load_project(filename)?;
+    let original = load_rom(&project.rom.to_string_lossy(), mapping, profiles)?;
+    let mut rom = original.clone();
+
+    let pointer_tables = project
+        .dialogues
+        .iter()
+        .map(|(name, dialogues)| {
+            pointer_table(&rom, &dialogues.location())
+                .map_err(|error| format!("dialogues {name}: {error}").into())
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    let space = FreeSpaceArgs {
+        free: project
+            .free
+            .iter()
+            .map(|region| (region.start, region.end))
+            .collect(),
+        detect_free: project.detect_free,
+        min_free: project.min_free,
+    };
+    let mut space = space.resolve(&rom, &pointer_tables)?;
+    for list in project.lists.values() {
+        if let (Some(start), Some(end)) = (rom.resolve(list.start), rom.resolve(list.end)) {
+            space.reserve(start..end);
+        }
+    }
 
-                    let song = maybe_hex::<u8>(&args[0]).unwrap();
-                    let volume = maybe_hex::<u8>(&args[1]).unwrap();
-                    file.write_u8(song)?;
-                    file.write_u8(volume)?;
-                }
-                "WaitForA" => file.write_u8(0x8A)?,
-                "Unknown8B" => {
-                    current_page = 99;
-                    file.write_u8(0x8B)?
-                }
-                "Unknown8C" => file.write_u8(0x8C)?,
-                "Unknown8D" => {
-                    assert_eq!(args.len(), 2);
+    let resources = project.dialogues.len() + project.lists.len();
+    let mut failures = 0;
+    for ((name, dialogues), pointers) in project.dialogues.iter().zip(&pointer_tables) {
+        println!("dialogues {name}:");
+        let result = load_tables(project.table(&dialogues.table), tables).and_then(|tables| {
+            compile_dialogues(
+                &dialogues.source.to_string_lossy(),
+                &mut rom,
+                pointers,
+                &mut space,
+                &tables,
+            )
+        });
+        if let Err(error) = result {
+            eprintln!("error: dialogues {name}: {error}");
+            failures += 1;
+        }
+    }
+    for (name, list) in &project.lists {
+        let result = load_tables(project.table(&list.table), tables).and_then(|tables| {
+            let start = resolve(&rom, list.start)?;
+            let end = resolve(&rom, list.end)?.max(start);
+            let data = compile_list(&list.source.to_string_lossy(), &tables)?
+                .into_values()
+                .flatten()
+                .collect::<Vec<_>>();
+            if data.len() > end - start {
+                return Err(format!(
+                    "the strings take {} bytes but the list at {} has room for {}",
+                    data.len(),
+                    describe(&rom, start),
+                    end - start
+                )
+                .into());
+            }
+            // the rest of the list becomes empty strings
+            rom.data[start..end].fill(0xFF);
+            rom.data[start..start + data.len()].copy_from_slice(&data);
+            println!(
+                "list {name}: {}, {} of {} bytes",
+                describe(&rom, start),
+                data.len(),
+                end - start
+            );
+            Ok(())
+        });
+        if let Err(error) = result {
+            eprintln!("error: list {name}: {error}");
+            failures += 1;
+        }
+    }
+    if failures > 0 {
+        return Err(format!(
+            "{failures} of {resources} resource(s) failed, {output} was not written"
+        )
+        .into());
+    }
 
-                    file.write_u8(0x8D)?;
+    write_rom(output, &original, &mut rom)
+}
 
-                    let unk1 = maybe_hex::<u8>(&args[0]).unwrap();
-                    let unk2 = maybe_hex::<u8>(&args[1]).unwrap();
-                    file.write_u8(unk1)?;
-                    file.write_u8(unk2)?;
-                }
-                "Unknown8E" => {
-                    assert_eq!(args.len(), 2);
+/// Decompiles every resource of the manifest `filename` from its ROM, or
+/// from `rom`, to the sources a build reads.
+fn extract(
+    filename: &str,
+    rom: Option<&str>,
+    force: bool,
+    mapping: Option<Mapping>,
+    profiles: &Profiles,
+    tables: &Tables,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let project = load_project(filename)?;
+    let rom = match rom {
+        Some(rom) => load_rom(rom, mapping, profiles)?,
+        None => load_rom(&project.rom.to_string_lossy(), mapping, profiles)?,
+    };
+
+    if !force {
+        let sources = project
+            .dialogues
+            .values()
+            .map(|dialogues| &dialogues.source);
+        let existing = sources
+            .chain(project.lists.values().map(|list| &list.source))
+            .filter(|source| source.exists())
+            .collect::<Vec<_>>();
+        if !existing.is_empty() {
+            for source in &existing {
+                eprintln!("error: {} already exists", source.display());
+            }
+            return Err("use --force to overwrite the sources".into());
+        }
+    }
 
-                    file.write_u8(0x8E)?;
+    let resources = project.dialogues.len() + project.lists.len();
+    let mut failures = 0;
+    for (name, dialogues) in &project.dialogues {
+        let result = load_tables(project.table(&dialogues.table), tables).and_then(|tables| {
+            let pointers = pointer_table(&rom, &dialogues.location())?;
+            decompile_dialogues(&rom, &pointers, &dialogues.source, &tables)
+        });
+        match result {
+            Ok(()) => println!(
+                "dialogues {name}: {} dialogue(s) in {}",
+                dialogues.count,
+                dialogues.source.display()
+            ),
+            Err(error) => {
+                eprintln!("error: dialogues {name}: {error}");
+                failures += 1;
+            }
+        }
+    }
+    for (name, list) in &project.lists {
+        let result = load_tables(project.table(&list.table), tables).and_then(|tables| {
+            if let Some(directory) = list.source.parent() {
+                fs::create_dir_all(directory)
+                    .map_err(|error| format!("{}: {error}", directory.display()))?;
+            }
+            let labels = list.labels.as_ref().map(|labels| labels.to_string_lossy());
+            let options = ListOptions {
+                output: Some(&list.source),
+                format: ListFormat::Json,
+                labels: labels.as_deref(),
+            };
+            let end = ListEnd::At(list.end);
+            print_array_of_strings(&rom, list.start, end, &options, &tables)
+        });
+        match result {
+            Ok(()) => println!("list {name}: {}", list.source.display()),
+            Err(error) => {
+                eprintln!("error: list {name}: {error}");
+                failures += 1;
+            }
+        }
+    }
+    if failures > 0 {
+        return Err(format!("{failures} of {resources} resource(s) failed").into());
+    }
 
-                    let unk1 = maybe_hex::<u8>(&args[0]).unwrap();
-                    let unk2 = maybe_hex::<u8>(&args[1]).unwrap();
-                    file.write_u8(unk1)?;
-                    file.write_u8(unk2)?;
-                }
-                "ChangeTextSpeed" => {
-                    assert_eq!(args.len(), 1);
+    Ok(())
+}
 
-                    file.write_u8(0x8F)?;
+/// Reads the manifest `filename`, or the one inside it if it's a directory.
+fn load_project(filename: &str) -> Result<Project, Box<dyn std::error::Error>> {
+    let mut path = PathBuf::from(filename);
+    if path.is_dir() {
+        path.push(MANIFEST);
+    }
+    Ok(Project::load(&path).map_err(|error| format!("{}: {error}", path.display()))?)
+}
 
-                    let speed = &args[0];
-                    let speed = match speed.as_str() {
-                        "Fast" => 0x84,
-                        "Slow" => 0x89,
-                        _ => panic!("Unknown speed: {speed}"),
-                    };
-                    file.write_u8(speed)?;
-                }
-                "Unknown90" => file.write_u8(0x90)?,
-                "Unknown91" => file.write_u8(0x91)?,
-                "SwitchFrame" => {
-                    assert_eq!(args.len(), 1);
-
-                    file.write_u8(0x92)?;
-
-                    let position = &args[0];
-                    let position = match position.as_str() {
-                        "Top" => 0x00,
-                        "Bottom" => 0x01,
-                        _ => panic!("Unknown position: {position}"),
-                    };
-                    file.write_u8(position)?;
-                }
-                "Unknown93" => file.write_u8(0x93)?,
-                "TimedWaitForA" => {
-                    assert_eq!(args.len(), 1);
+/// Reads the character tables `filename`, or uses `default` when there is none.
+fn load_tables(
+    filename: Option<&Path>,
+    default: &Tables,
+) -> Result<Tables, Box<dyn std::error::Error>> {
+    match filename {
+        Some(filename) => Ok(Tables::load(&filename.to_string_lossy())
+            .map_err(|error| format!("{}: {error}", filename.display()))?),
+        None => Ok(default.clone()),
+    }
+}
 
-                    let frames = maybe_hex::<u16>(&args[0]).unwrap();
+/// Writes `rom` to `output`, or a patch from `original` to `rom` if `output`
+/// ends with `.ips` or `.bps`, after fixing its checksum. Unlike IPS patches,
+/// BPS patches leave out the copier header, as other BPS tools expect.
+fn write_rom(
+    output: &str,
+    original: &Rom,
+    rom: &mut Rom,
+) -> Result<(), Box<dyn std::error::Error>> {
+    rom.fix_checksum();
+    let extension = Path::new(output)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    let data = match extension.as_deref() {
+        Some("ips") => create_ips(&original.to_file(), &rom.to_file())?,
+        Some("bps") => create_bps(&original.data, &rom.data),
+        _ => rom.to_file(),
+    };
+    fs::write(output, data).map_err(|error| format!("{output}: {error}"))?;
+    Ok(())
+}
 
-                    file.write_u8(0x94)?;
-                    file.write_u16::<LittleEndian>(frames)?;
-                }
-                "Unknown95" => file.write_u8(0x95)?,
-                _ => panic!("{command}"),
-            }
+fn identify(rom: &Rom, profiles: &Profiles) {
+    let header = if rom.copier_header.is_empty() {
+        "no copier header"
+    } else {
+        "copier header"
+    };
+    println!("{} bytes, {header}, {:?}", rom.data.len(), rom.mapping);
+    println!("title:    {:?}", rom.title().unwrap_or_default());
+    println!("version:  {}", rom.version().unwrap_or_default());
+    println!("CRC32:    {:08X}", crc32(&rom.data));
+    if let Some((checksum, _)) = rom.stored_checksum() {
+        let status = if checksum == rom.checksum() {
+            "correct"
         } else {
-            let c = script[index];
-            if c == '\n' {
-                // do nothing for now
-            } else {
-                for page in 0..4 {
-                    if let Some(id) = DIALOGUES_CHARACTERS[page].iter().position(|&r| r == c) {
-                        if current_page != page {
-                            file.write_u8(0x00)?;
-                            file.write_u8(page as u8 + 0x11)?;
-                            current_page = page;
-                        }
-                        file.write_u8(id as u8)?;
-                        break;
-                    }
+            "incorrect"
+        };
+        println!("checksum: {checksum:#06X} ({status})");
+    }
 
-                    if page == 3 {
-                        if c == '\\' {
-                            index += 1;
-                            assert_eq!(script[index], 'n');
-                            file.write_u8(0x00)?;
-                            file.write_u8(0x01)?;
-                            break;
-                        } else {
-                            panic!("LOL: {c} / {}", script[index + 1]);
-                        }
-                    }
-                }
-            }
-        }
-        index += 1;
+    let identification = profiles.identify(rom);
+    let Some((name, profile)) = identification.profile() else {
+        println!("profile:  none");
+        return;
+    };
+    let exact = match identification {
+        Identification::Exact(..) => "",
+        _ => " (by its header)",
+    };
+    println!("profile:  {name}{exact}");
+    for (name, table) in &profile.dialogues {
+        let pointers = rom
+            .resolve(table.pointers)
+            .map_or(format!("{:?}", table.pointers), |offset| {
+                describe(rom, offset)
+            });
+        println!("  dialogues {name}: {} pointers at {pointers}", table.count);
+    }
+    for (name, list) in &profile.lists {
+        let start = rom
+            .resolve(list.start)
+            .map_or(format!("{:?}", list.start), |offset| describe(rom, offset));
+        println!("  list {name}: at {start}");
+    }
+}
+
+fn check_checksum(
+    filename: &str,
+    rom: &mut Rom,
+    fix: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (checksum, complement) = rom
+        .stored_checksum()
+        .ok_or("the ROM is too small to have a cartridge header")?;
+    let expected = rom.checksum();
+    println!(
+        "{:?}, checksum {checksum:#06X}, complement {complement:#06X}",
+        rom.mapping
+    );
+
+    if checksum == expected && complement == !expected {
+        println!("the checksum is correct");
+    } else if fix {
+        rom.fix_checksum();
+        fs::write(filename, rom.to_file()).map_err(|error| format!("{filename}: {error}"))?;
+        println!("fixed the checksum to {expected:#06X}");
+    } else {
+        return Err(
+            format!("the checksum should be {expected:#06X}, use --fix to correct it").into(),
+        );
     }
 
     Ok(())
 }
 
+fn verify_dialogue(
+    rom: &Rom,
+    offset: Position,
+    tables: &Tables,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let offset = resolve(rom, offset)?;
+    let data = &rom.data[offset..];
+    let spans = decode_dialogue_lenient(data, tables)
+        .map_err(|error| located(rom, error.rebase(offset)))?;
+    let length = spans.last().map_or(0, |(span, _)| span.end);
+    let original = &data[..length];
+
+    let commands = spans
+        .iter()
+        .map(|(_, command)| command.clone())
+        .collect::<Vec<_>>();
+    let script = format_dialogue(&commands, tables, false);
+    let reparsed = parse_dialogue(&script, tables).map_err(|errors| errors[0].to_string())?;
+    let recompiled = encode_dialogue(&reparsed, tables)?;
+
+    let Some(index) =
+        (0..original.len().max(recompiled.len())).find(|&i| original.get(i) != recompiled.get(i))
+    else {
+        println!(
+            "{}: {length} bytes, identical after recompilation",
+            describe(rom, offset)
+        );
+        return Ok(());
+    };
+
+    let byte = |bytes: &[u8]| {
+        bytes
+            .get(index)
+            .map_or("end".into(), |b| format!("{b:02X}"))
+    };
+    println!(
+        "{}: expected {}, recompiled {} (byte {index} of {length})",
+        describe(rom, offset + index),
+        byte(original),
+        byte(&recompiled)
+    );
+
+    let window = index.saturating_sub(8)..index + 8;
+    let hex = |bytes: &[u8]| {
+        bytes[window.start.min(bytes.len())..window.end.min(bytes.len())]
+            .iter()
+            .map(|b| format!("{b:02X}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    println!("  original:   {}", hex(original));
+    println!("  recompiled: {}", hex(&recompiled));
+
+    let current = spans
+        .iter()
+        .position(|(span, _)| span.contains(&index))
+        .unwrap_or(spans.len() - 1);
+    for (i, (span, command)) in spans.iter().enumerate() {
+        if current.abs_diff(i) <= 2 {
+            let marker = if i == current { '>' } else { ' ' };
+            let command = format_dialogue(std::slice::from_ref(command), tables, false);
+            println!(
+                "{marker} {}: {}",
+                describe(rom, offset + span.start),
+                command.trim_end()
+            );
+        }
+    }
+
+    Err(format!("dialogue at {} does not round-trip", describe(rom, offset)).into())
+}
+
+/// Where a list decompiled by [`print_array_of_strings`] ends.
+#[derive(Debug, Clone, Copy)]
+enum ListEnd {
+    At(Position),
+    /// After this many strings.
+    Count(usize),
+    /// Before the first code that isn't a character.
+    Detect,
+}
+
+struct ListOptions<'a> {
+    output: Option<&'a Path>,
+    format: ListFormat,
+    labels: Option<&'a str>,
+}
+
 fn print_array_of_strings(
-    filename: &str,
-    begin: u64,
-    end: u64,
+    rom: &Rom,
+    begin: Position,
+    end: ListEnd,
+    options: &ListOptions,
+    tables: &Tables,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut rom = File::open(filename)?;
-    rom.seek(SeekFrom::Start(begin))?;
-    let mut buffer = vec![0u8; (end - begin) as usize];
-    rom.read(&mut buffer)?;
-
-    let mut cursor = Cursor::new(buffer);
-    print!("\"");
-    while let Ok(data) = cursor.read_u16::<LittleEndian>() {
-        if data == 0xFFFF {
-            println!("\"");
-            print!("\"");
-        } else if data == 0xFFFE {
-            print!("\\r"); // "line break storage/memory"?
-        } else if data == 0xFFFD {
-            print!("\\n");
-        } else {
-            let index = data as usize;
-            if index >= LISTS_CHARACTERS.len() {
-                println!("!! {index:#X} !!");
+    let begin = resolve(rom, begin)?;
+    let end = match end {
+        ListEnd::At(end) => {
+            let end = resolve(rom, end)?.max(begin);
+            // an unterminated string would get a terminator, and no longer fit
+            let length = terminated_length(&rom.data[begin..end]);
+            if begin + length < end {
+                eprintln!(
+                    "warning: the list ends at {}, after its last terminator, leaving out {} byte(s)",
+                    describe(rom, begin + length),
+                    end - begin - length
+                );
             }
-            assert!(index < LISTS_CHARACTERS.len());
-            let c = LISTS_CHARACTERS[index];
-            print!("{c}");
+            begin + length
         }
-    }
-    print!("\"");
+        ListEnd::Count(count) => {
+            let length = list_length(&rom.data[begin..], count).ok_or_else(|| {
+                format!(
+                    "the ROM ends before {count} string(s) from {}",
+                    describe(rom, begin)
+                )
+            })?;
+            begin + length
+        }
+        ListEnd::Detect => {
+            let (length, stop) = detect_list_length(&rom.data[begin..], tables);
+            let end = begin + length;
+            match stop {
+                Some(stop) => {
+                    let code =
+                        u16::from_le_bytes([rom.data[begin + stop], rom.data[begin + stop + 1]]);
+                    eprintln!(
+                        "the list ends at {}: {code:#06X} at {} is not a character",
+                        describe(rom, end),
+                        describe(rom, begin + stop)
+                    );
+                }
+                None => eprintln!(
+                    "the list ends at {} (every code up to the end of the ROM is a character)",
+                    describe(rom, end)
+                ),
+            }
+            end
+        }
+    };
+    let strings = decode_list(&rom.data[begin..end], tables);
+
+    let (text, extension) = match options.format {
+        ListFormat::Text => {
+            let strings = strings
+                .iter()
+                .map(|string| format!("\"{}\"\n", string.replace('\r', "\\r").replace('\n', "\\n")))
+                .collect::<String>();
+            (strings, "txt")
+        }
+        ListFormat::Json => {
+            let labels = match options.labels {
+                Some(labels) => read_labels(labels, strings.len())?,
+                None => (0..strings.len()).map(|i| format!("string_{i}")).collect(),
+            };
+            let strings = labels.into_iter().zip(strings).collect::<IndexMap<_, _>>();
+            (serde_json::to_string_pretty(&strings)? + "\n", "json")
+        }
+    };
+    write_output(
+        options.output,
+        &format!("list_{begin:06X}.{extension}"),
+        &text,
+    )?;
 
     Ok(())
 }
 
-fn compile_array_of_string(filename: &str, output: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let strings = read_to_string(filename)?;
-    let strings: IndexMap<String, String> = serde_json::from_str(&strings).unwrap();
+/// Reads one label per line, ignoring blank lines.
+fn read_labels(filename: &str, count: usize) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let labels = read_to_string(filename).map_err(|error| format!("{filename}: {error}"))?;
+    let labels = labels
+        .lines()
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    if labels.len() != count {
+        return Err(format!(
+            "{filename} has {} label(s) but the list has {count} string(s)",
+            labels.len()
+        )
+        .into());
+    }
+    let mut seen = std::collections::HashSet::new();
+    if let Some(label) = labels.iter().find(|label| !seen.insert(label.as_str())) {
+        return Err(format!("{filename}: label `{label}` appears twice").into());
+    }
 
-    let mut output_file = File::create(output)?;
+    Ok(labels)
+}
 
-    for (label, jp_string) in strings {
-        writeln!(output_file, "{label}:")?;
+/// Encodes the strings of the JSON file `filename` as they are stored in the
+/// ROM, with their labels.
+fn compile_list(
+    filename: &str,
+    tables: &Tables,
+) -> Result<IndexMap<String, Vec<u8>>, Box<dyn std::error::Error>> {
+    let strings = read_to_string(filename).map_err(|error| format!("{filename}: {error}"))?;
+    let strings: IndexMap<String, String> =
+        serde_json::from_str(&strings).map_err(|error| format!("{filename}: {error}"))?;
+
+    strings
+        .into_iter()
+        .map(|(label, string)| {
+            let words = encode_string(&string, tables)
+                .map_err(|error| format!("{filename}: {label}: {error}"))?;
+            let bytes = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+            Ok((label, bytes))
+        })
+        .collect()
+}
 
-        write!(output_file, ".db")?;
-        for c in jp_string.chars() {
-            if c == '\n' {
-                write!(output_file, " $FD $FF")?;
-            } else if c == '\r' {
-                write!(output_file, " $FE $FF")?;
-            } else {
-                let index = LISTS_CHARACTERS
-                    .iter()
-                    .position(|&r| r == c)
-                    .expect(&format!("{c}???"));
-                write!(output_file, " ${:02X} ${:02X}", index & 0xFF, index >> 8)?;
+fn compile_array_of_string(
+    filename: &str,
+    output: &str,
+    format: CompiledListFormat,
+    asm: Option<Dialect>,
+    offsets: Option<&str>,
+    tables: &Tables,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let strings = compile_list(filename, tables)?;
+
+    match format {
+        CompiledListFormat::Asm => {
+            let dialect = asm.unwrap_or(Dialect::Plain);
+            let mut source = String::new();
+            for (label, bytes) in &strings {
+                let words = bytes
+                    .chunks_exact(2)
+                    .map(|word| u16::from_le_bytes([word[0], word[1]]))
+                    .collect::<Vec<_>>();
+                source += &format!("{label}:\n{}", dialect.words(&words));
             }
+            fs::write(output, source).map_err(|error| format!("{output}: {error}"))?;
+        }
+        CompiledListFormat::Binary => {
+            let data = strings.values().flatten().copied();
+            fs::write(output, data.collect::<Vec<_>>())
+                .map_err(|error| format!("{output}: {error}"))?;
         }
-        writeln!(output_file, " $FF $FF")?;
+    }
+
+    if let Some(offsets) = offsets {
+        let mut text = String::new();
+        let mut offset = 0;
+        for (label, bytes) in &strings {
+            text += &format!("{offset:#06X} {label}\n");
+            offset += bytes.len();
+        }
+        fs::write(offsets, text).map_err(|error| format!("{offsets}: {error}"))?;
     }
 
     Ok(())
 }
-
-#[rustfmt::skip]
-const LISTS_CHARACTERS: [char; 283] = [
-    /*  00 */ 'あ', 'い', 'う', 'え', 'お', 'か', 'き', 'く', 'け', 'こ', 'さ', 'し', 'す', 'せ', 'そ', 'た',
-    /*  10 */ 'ち', 'つ', 'て', 'と', 'な', 'に', 'ぬ', 'ね', 'の', 'は', 'ひ', 'ふ', 'へ', 'ほ', 'ま', 'み',
-    /*  20 */ 'む', 'め', 'も', 'や', 'ゆ', 'よ', 'ら', 'り', 'る', 'れ', 'ろ', 'わ', 'を', 'ん', 'が', 'ぎ',
-    /*  30 */ 'ぐ', 'げ', 'ご', 'ざ', 'じ', 'ず', 'ぜ', 'ぞ', 'だ', 'ぢ', 'づ', 'で', 'ど', 'ば', 'び', 'ぶ',
-    /*  40 */ 'べ', 'ぼ', 'ぱ', 'ぴ', 'ぷ', 'ぺ', 'ぽ', '_', '_', '_', '_', '_', 'ゃ', 'っ', 'ゅ', 'ょ',
-    /*  50 */ 'ア', 'イ', 'ウ', 'エ', 'オ', 'カ', 'キ', 'ク', 'ケ', 'コ', 'サ', 'シ', 'ス', 'セ', 'ソ', 'タ',
-    /*  60 */ 'チ', 'ツ', 'テ', 'ト', 'ナ', 'ニ', 'ヌ', 'ネ', 'ノ', 'ハ', 'ヒ', 'フ', 'ヘ', 'ホ', 'マ', 'ミ',
-    /*  70 */ 'ム', 'メ', 'モ', 'ヤ', 'ユ', 'ヨ', 'ラ', 'リ', 'ル', 'レ', 'ロ', 'ワ', 'ン', 'ガ', 'ギ', 'グ',
-    /*  80 */ 'ゲ', 'ゴ', 'ザ', 'ジ', 'ズ', 'ゼ', 'ゾ', 'ダ', 'ヂ', 'ヅ', 'デ', 'ド', 'バ', 'ビ', 'ブ', 'ベ',
-    /*  90 */ 'ボ', 'パ', 'ピ', 'プ', 'ペ', 'ポ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ', 'ャ', 'ッ', 'ュ', 'ョ', '０',
-    /*  A0 */ '１', '２', '３', '４', '５', '６', '７', '８', '９', 'Ｈ', 'Ｐ', 'Ｌ', 'Ｖ', '@' /*EXのE*/, 'µ' /*EXのX*/, 'Ｍ',
-    /*  B0 */ '／', 'Ｄ', 'Ｒ', '店', 'Ｙ', 'Ｎ', 'Ｅ', 'Ｓ', 'Ａ', 'Ｗ', '％', 'Ｘ', '「', '」', '＿', '・',
-    /*  C0 */ '（', '）', ' ', 'ー', '！', '兵', '書', '石', '竜', '杖', '星', '特', '輸', '送', '隊', '状',
-    /*  D0 */ '況', '終', '断', '話', '門', '道', '魔', '運', '所', '持', '品', '捨', '交', '換', '預', '闘',
-    /*  E0 */ '技', '場', '防', '城', '装', '使', '用', '攻', '撃', '速', '守', '備', '効', '幸', '第', '章',
-    /*  F0 */ 'Ｔ', 'Ｏ', '部', '騎', '＋', '宝', '箱', 'Ｂ', '器', '回', '復', '系', '？', '士', '：', '命',
-    /* 100 */ '中', '重', '必', '殺', '武', '╔', '╦', '╗', '╠', '╣', '╚', '╩', '╝', '⇔', '❏', '╭',
-    /* 110 */ '╮', '╰', '╯', '┏','┳', '┓', '┣', '┫', '┗', '┻', '┛',
-];
-
-#[rustfmt::skip]
-const DIALOGUES_CHARACTERS: [[char; 256]; 4] = [
-    [   // 11
-        /* 00 */ 'X', 'あ', 'い', 'う', 'え', 'お', 'か', 'き', 'く', 'け', 'こ', 'さ', 'し', 'す', 'せ', 'そ',
-        /* 10 */ 'た', 'ち', 'つ', 'て', 'と', 'な', 'に', 'ぬ', 'ね', 'の', 'は', 'ひ', 'ふ', 'へ', 'ほ', 'ま',
-        /* 20 */ 'み', 'む', 'め', 'も', 'や', 'ゆ', 'よ', 'ら', 'り', 'る', 'れ', 'ろ', 'わ', 'を', 'ん', 'が',
-        /* 30 */ 'ぎ', 'ぐ', 'げ', 'ご', 'ざ', 'じ', 'ず', 'ぜ', 'ぞ', 'だ', 'ぢ', 'づ', 'で', 'ど', 'ば', 'び',
-        /* 40 */ 'ぶ', 'べ', 'ぼ', 'ぱ', 'ぴ', 'ぷ', 'ぺ', 'ぽ', 'ぁ', 'ぃ', '_', 'ぇ', 'ぉ', 'ゃ', 'っ', 'ゅ',
-        /* 50 */ 'ょ', 'ア', 'イ', 'ウ', 'エ', 'オ', 'カ', 'キ', 'ク', 'ケ', 'コ', 'サ', 'シ', 'ス', 'セ', 'ソ',
-        /* 60 */ 'タ', 'チ', 'ツ', 'テ', 'ト', 'ナ', 'ニ', 'ヌ', 'ネ', 'ノ', 'ハ', 'ヒ', 'フ', 'ヘ', 'ホ', 'マ',
-        /* 70 */ 'ミ', 'ム', 'メ', 'モ', 'ヤ', 'ユ', 'ヨ', 'ラ', 'リ', 'ル', 'レ', 'ロ', 'ワ', 'ン', 'ガ', 'ギ',
-        /* 80 */ 'グ', 'ゲ', 'ゴ', 'ザ', 'ジ', 'ズ', 'ゼ', 'ゾ', 'ダ', 'ヂ', 'ヅ', 'デ', 'ド', 'バ', 'ビ', 'ブ',
-        /* 90 */ 'ベ', 'ボ', 'パ', 'ピ', 'プ', 'ペ', 'ポ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ', 'ャ', 'ッ', 'ュ', 'ョ',
-        /* A0 */ '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '神', '何', '会', '賊', '大', '男',
-        /* B0 */ '元', '気', '出', '_', '今', '皇', '帝', '聖', '_', 'ー', '勇', '者', '？', '苦', '加', '争',
-        /* C0 */ '「', '」', '（', '）', ' ', '！', '攻', '撃', '力', '守', '備', '魔', '竜', '石', '団', '闘',
-        /* D0 */ '技', '場', '店', '必', '殺', '一', '道', '書', '杖', '系', 'Ｍ', '星', '話', '兵', '士', '使',
-        /* E0 */ '用', '運', '_', '法', '軍', '白', '火', '飛', '地', '暗', '黒', '進', '城', '海', '人', '騎',
-        /* F0 */ '.', '王', '子', '女', '様', '父', '行', '戦', '来', '・', '母', '山', '集', '♥', '剣', '国',
-    ],
-    [   // 12
-        /* 00 */ '_', '待', '祖', '老', '東', '方', '辺', '境', '小', '援', '助', '_', '親', '討', '身', '愛',
-        /* 10 */ '姉', '姫', '才', '々', '二', '年', '日', '対', '岸', '街', '彼', '解', '放', '島', '南', '北',
-        /* 20 */ '近', '村', '旅', '悪', '_', '知', '陸', '草', '原', '建', '以', '他', '_', '占', '領', '_',
-        /* 30 */ '名', '弟', '半', '公', '数', '_', '前', '主', '破', '同', '盟', '内', '財', '宝', '敢', '箱',
-        /* 40 */ '俺', '奴', '中', '_', '_', '下', '都', '谷', '築', '無', '砦', '最', '将', '指', '揮', '唯',
-        /* 50 */ '門', '始', '後', '退', '入', '休', '_', '_', '金', '自', '治', '好', '意', '的', '傭', '久',
-        /* 60 */ '_', '持', '願', '質', '捕', '要', '塞', '決', '罠', '言', '見', '性', '恐', '思', '上', '悲',
-        /* 70 */ '辛', '分', '代', '救', '率', '末', '千', '宮', '物', '多', '達', '目', '市', '家', '統', '合',
-        /* 80 */ '事', '世', '三', '種', '武', '器', '_', '血', '_', '貴', '_', '_', '立', '偉', '手', '_',
-        /* 90 */ '敗', '向', '_', '水', '_', '美', '荒', '迎', '全', '部', '隊', '取', '罪', '賢', '光', '殿',
-        /* A0 */ '_', '止', '英', '雄', '強', '背', '_', '未', '開', '族', '切', '従', '現', '弱', '復', '活',
-        /* B0 */ '受', '_', '誇', '滅', '亡', '古', '高', '度', '絶', '動', '塔', '司', '祭', '失', '追', '込',
-        /* C0 */ '包', '囲', '所', '突', '散', '残', '選', '命', '_', '歩', '盗', '港', '＜', '＞', '心', '着',
-        /* D0 */ '生', '足', '移', '文', '明', '望', '時', '平', '和', '支', '配', '激', '界', '秘', '野', '乱',
-        /* E0 */ '域', '_', '_', '_', '打', '倒', '、', '。', '死', '槍', '弓', '斧', '民', '位', '先', '利',
-        /* F0 */ '兄', '氷', '信', '真', '正', '義', '忠', '誠', '臣', '婚', '礼', '_', '伯', '伝', '説', '炎',
-    ],
-    [   // 13
-        /* 00 */ '_', '紋', '章', '_', '反', '断', '_', '_', '初', '_', '消', '第', '町', '隷', '天', '空',
-        /* 10 */ '_', '遠', '征', '連', '去', '嘆', '_', '_', '墓', '_', '再', '奪', '回', '_', '帰', '_',
-        /* 20 */ '壇', '制', '圧', '病', '終', '_', '相', '商', '勝', '負', '続', '表', '示', 'B', '賞', '_',
-        /* 30 */ '然', '異', '壊', '護', '熱', '識', '各', '装', '西', '妹', '安', '貨', '侵', '玉', '座', '準',
-        /* 40 */ '鉄', '銀', '屋', '百', '封', '印', '盾', '電', '間', '橋', '_', '脱', '防', '壮', '冷', '昔',
-        /* 50 */ '途', '官', '_', '狂', '_', '希', '重', '栄', '発', '_', '語', '呋', '体', '令', '精', '鋭',
-        /* 60 */ '邪', '協', '接', '完', '政', '緒', '節', '砂', '漠', '灼', '太', '陽', '当', '党', '本', '能',
-        /* 70 */ '少', '作', '幸', '理', '新', '夫', '逃', '略', '不', '永', '参', '惨', '七', '減', '成', '共',
-        /* 80 */ '訪', '_', '感', '与', '挟', '長', '具', '担', '急', '由', '耳', '産', '_', '_', '超', '仲',
-        /* 90 */ '変', '務', '償', '売', '買', '声', '教', '恋', '員', '服', '修', '屈', '注', '巨', '情', '馬',
-        /* A0 */ '徒', '森', '得', '機', '軽', '経', '験', '昇', '格', '扱', '有', '若', '涙', '効', '特', '殊',
-        /* B0 */ '離', '在', '存', '呪', '_', '仕', '御', '興', '蛮', '化', '外', '抗', '抵', '処', '刑', '_',
-        /* C0 */ '陛', '獄', '可', '個', '室', '危', '択', '結', '引', '_', '旗', '密', '_', '_', '_', '赤',
-        /* D0 */ '短', '乗', '_', '_', '_', '逆', '派', '遣', '_', '踊', '迷', '_', '学', '勉', '伐', '_',
-        /* E0 */ '限', '議', '_', '_', '果', '保', '害', '期', '_', '_', '岩', '別', '想', '_', '_', '_',
-        /* F0 */ '_', '_', '_', '_', '_', '衛', '息', '類', '_', '実', '_', '非', '術', '_', '鋼', '継',
-    ],
-    [   // 14
-        /* 00 */ '_', '承', '_', '_', '増', '_', '_', '_', '_', '風', '_', '_', '_', '_', '_', '定',
-        /* 10 */ '_', '戻', '請', '_', '_', '任', '念', '忘', '涯', '_', '頼', '姿', '_', '遊', '_', '_',
-        /* 20 */ '良', '_', '_', '師', '捨', '_', '託', '娘', '告', '_', '予', '_', '努', '焼', '役', '仮',
-        /* 30 */ '面', '暮', '我', '堂', '腕', '_', '_', '業', '五', '_', '_', '_', '_', '_', '応', '_',
-        /* 40 */ '_', '_', '_', '_', '_', '_', '_', '報', '皆', '払', '混', '双', '口', '降', '伏', '_',
-        /* 50 */ '_', '幅', '悔', '毎', '牢', '肉', '到', '_', '妃', '_', '迫', '求', '職', '魂', '紙', '_',
-        /* 60 */ '通', '供', '許', '局', '送', '絡', '孫', '扉', '約', '束', '_', '万', 'ヲ', '左', '右', '峠',
-        /* 70 */ '闇', '稼', '_', '台', '_', '_', '覇', '_', '怒', '威', '付', '_', '_', '_', '_', '_',
-        /* 80 */ '_', '_', '_', '_', '細', '_', '_', '川', '_', '_', '呼', '起', '_', '_', '泣', '欲',
-        /* 90 */ '_', '_', '_', '段', '階', '_', '_', '_', '_', '_', '_', '_', '寸', '_', '_', '_',
-        /* A0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
-        /* B0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
-        /* C0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
-        /* D0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
-        /* E0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
-        /* F0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
-    ],
-];
-
-const PORTRAITS: [&str; 256] = [
-    "Marth",
-    "Ceada",
-    "Jagen",
-    "Cain",
-    "Abel",
-    "Draug",
-    "Gordin",
-    "Rickard",
-    "Unknown 008",
-    "Bord",
-    "Barst",
-    "Cord",
-    "Castor",
-    "Ogma",
-    "Unknown 014",
-    "Julian",
-    "Lena",
-    "Merric",
-    "Navarre",
-    "Hardin",
-    "Wolf",
-    "Sedgar",
-    "Roshea",
-    "Vyland",
-    "Bantu",
-    "Caesar",
-    "Radd",
-    "Midia",
-    "Catria",
-    "Maria",
-    "Minerva",
-    "Linde",
-    "Jeorge",
-    "Dolph",
-    "Macellan",
-    "Tomas",
-    "Boah",
-    "Arran",
-    "Samson",
-    "Unknown 039",
-    "Xane",
-    "Wendell",
-    "Est",
-    "Astram",
-    "Matthis",
-    "Unknown 045",
-    "Unknown 046",
-    "Palla",
-    "Tiki",
-    "Lorenz",
-    "Elice",
-    "Gotoh",
-    "Gazzak",
-    "Unknown 053",
-    "Unknown 054",
-    "Gomer",
-    "Unknown 056",
-    "Unknown 057",
-    "Merach",
-    "Harmein",
-    "Emereus",
-    "Unknown 061",
-    "Kannival",
-    "Unknown 063",
-    "Zharov",
-    "Khozen",
-    "Unknown 066",
-    "Unknown 067",
-    "Jiol",
-    "Volzhin",
-    "Hollstadt",
-    "Unknown 071",
-    "Hyman",
-    "Xemcel",
-    "Unknown 074",
-    "Camus",
-    "Michalis",
-    "Morzas",
-    "Gharnef",
-    "Medeus",
-    "Unknown 080",
-    "Nehring",
-    "Unknown 082",
-    "Unknown 083",
-    "Unknown 084",
-    "Unknown 085",
-    "Unknown 086",
-    "Unknown 087",
-    "Unknown 088",
-    "Unknown 089",
-    "Unknown 090",
-    "Unknown 091",
-    "Unknown 092",
-    "Unknown 093",
-    "Unknown 094",
-    "Unknown 095",
-    "Malledus",
-    "Nyna",
-    "King of Talys",
-    "King of Aurelis",
-    "Villager (Bald Dad #1)",
-    "Villager (Bald Dad #2)",
-    "Villager (Bald Dad #3)",
-    "Villager (old man)",
-    "Villager (old woman)",
-    "Villager (Uncle)",
-    "Villager (auntie)",
-    "Villager (male)",
-    "Villager (female)",
-    "Unknown 109",
-    "Unknown 110",
-    "Strange old woman",
-    "Unknown 112",
-    "Unknown 113",
-    "Slave Trader",
-    "Linda (slave)",
-    "Enemy Soldier",
-    "Cecil",
-    "Luke",
-    "Roderick",
-    "Ryan",
-    "Warren",
-    "Lumel",
-    "Lykke",
-    "Yuliya",
-    "Jubelo",
-    "Sirius",
-    "Gail",
-    "Marisha",
-    "Torus",
-    "Samuel",
-    "Lang",
-    "Phina",
-    "Dall",
-    "Yodel",
-    "Arlen",
-    "Eibel",
-    "Sheena",
-    "Willow",
-    "Marth (book 2)",
-    "Marth determined (book 2)",
-    "Linde (book 2)",
-    "Merric (book 2)",
-    "Tiki (book 2)",
-    "Ceada (book 2)",
-    "Medeus (dragon)",
-    "Minerva (book 2)",
-    "Hardin (book 2)",
-    "Unknown 148",
-    "Unknown 149",
-    "Unknown 150",
-    "Unknown 151",
-    "Unknown 152",
-    "Unknown 153",
-    "Unknown 154",
-    "Unknown 155",
-    "Unknown 156",
-    "Unknown 157",
-    "Unknown 158",
-    "Unknown 159",
-    "Unknown 160",
-    "Unknown 161",
-    "Unknown 162",
-    "Unknown 163",
-    "Unknown 164",
-    "Unknown 165",
-    "Unknown 166",
-    "Unknown 167",
-    "Unknown 168",
-    "Unknown 169",
-    "Unknown 170",
-    "Unknown 171",
-    "Unknown 172",
-    "Unknown 173",
-    "Unknown 174",
-    "Unknown 175",
-    "Unknown 176",
-    "Unknown 177",
-    "Unknown 178",
-    "Unknown 179",
-    "Unknown 180",
-    "Unknown 181",
-    "Unknown 182",
-    "Unknown 183",
-    "Unknown 184",
-    "Unknown 185",
-    "Unknown 186",
-    "Unknown 187",
-    "Unknown 188",
-    "Unknown 189",
-    "Unknown 190",
-    "Unknown 191",
-    "Unknown 192",
-    "Unknown 193",
-    "Unknown 194",
-    "Unknown 195",
-    "Unknown 196",
-    "Unknown 197",
-    "Unknown 198",
-    "Unknown 199",
-    "Unknown 200",
-    "Unknown 201",
-    "Unknown 202",
-    "Unknown 203",
-    "Unknown 204",
-    "Unknown 205",
-    "Unknown 206",
-    "Unknown 207",
-    "Unknown 208",
-    "Unknown 209",
-    "Unknown 210",
-    "Unknown 211",
-    "Unknown 212",
-    "Unknown 213",
-    "Unknown 214",
-    "Unknown 215",
-    "Unknown 216",
-    "Unknown 217",
-    "Unknown 218",
-    "Unknown 219",
-    "Unknown 220",
-    "Unknown 221",
-    "Unknown 222",
-    "Unknown 223",
-    "Unknown 224",
-    "Unknown 225",
-    "Unknown 226",
-    "Unknown 227",
-    "Unknown 228",
-    "Unknown 229",
-    "Unknown 230",
-    "Unknown 231",
-    "Unknown 232",
-    "Unknown 233",
-    "Unknown 234",
-    "Unknown 235",
-    "Unknown 236",
-    "Unknown 237",
-    "Unknown 238",
-    "Unknown 239",
-    "Unknown 240",
-    "Unknown 241",
-    "Unknown 242",
-    "Unknown 243",
-    "Unknown 244",
-    "Unknown 245",
-    "Unknown 246",
-    "Unknown 247",
-    "Unknown 248",
-    "Unknown 249",
-    "Unknown 250",
-    "Unknown 251",
-    "Unknown 252",
-    "Unknown 253",
-    "Unknown 254",
-    "Unknown 255",
-];
@@ -0,0 +1,259 @@
+use crate::error::Error;
+use clap_num::maybe_hex;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Opcodes handled by the decoder itself rather than by an [`Opcode`]: `[End]`,
+/// `\n` and the page switches.
+pub const END: u8 = 0x00;
+pub const NEW_LINE: u8 = 0x01;
+pub const PAGES: RangeInclusive<u8> = 0x11..=0x14;
+
+/// Description of a dialogue command: its name in scripts and the layout of
+/// the bytes following the opcode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Opcode {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<Argument>,
+    /// When decompiling, the text following the command stays on the same line.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub inline: bool,
+    /// The game forgets the current page after this command, so the next
+    /// character always needs a page switch.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub resets_page: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Argument {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: ArgumentKind,
+    /// `[shift, width]` of a value sharing a byte with its neighbours. A new
+    /// byte is read for the argument at shift 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bits: Option<[u8; 2]>,
+    /// Decompile the value in hexadecimal.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub hex: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ArgumentKind {
+    U8,
+    /// A little-endian word.
+    U16,
+    /// A byte with named values.
+    Enum {
+        values: IndexMap<String, Byte>,
+    },
+    /// A byte that always has this value. It doesn't appear in scripts.
+    Fixed {
+        value: Byte,
+    },
+    /// A portrait ID, written with the names of the portrait table.
+    Portrait,
+}
+
+/// A byte, written in JSON as a number or as a string like `"0x2C"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Byte(pub u8);
+
+impl Serialize for Byte {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:#04X}", self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Byte {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Number(u8),
+            Text(String),
+        }
+
+        match Value::deserialize(deserializer)? {
+            Value::Number(byte) => Ok(Byte(byte)),
+            Value::Text(text) => maybe_hex::<u8>(&text)
+                .map(Byte)
+                .map_err(|_| serde::de::Error::custom(format!("`{text}` is not a byte"))),
+        }
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl Opcode {
+    /// The arguments written in scripts, whose values are the `args` of a
+    /// [`DialogueCommand::Command`](crate::DialogueCommand::Command).
+    pub fn visible_args(&self) -> impl Iterator<Item = &Argument> {
+        self.args.iter().filter(|arg| arg.is_visible())
+    }
+
+    /// Position of the argument called `name` among [`Opcode::visible_args`].
+    pub fn arg_index(&self, name: &str) -> Option<usize> {
+        self.visible_args().position(|arg| arg.name == name)
+    }
+}
+
+impl Argument {
+    /// The value called `name` of an enum argument.
+    pub fn value(&self, name: &str) -> Option<u16> {
+        match &self.kind {
+            ArgumentKind::Enum { values } => values.get(name).map(|value| value.0 as u16),
+            _ => None,
+        }
+    }
+
+    /// The name of `value` for an enum argument.
+    pub fn value_name(&self, value: u16) -> Option<&str> {
+        match &self.kind {
+            ArgumentKind::Enum { values } => values
+                .iter()
+                .find(|(_, v)| v.0 as u16 == value)
+                .map(|(name, _)| name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Whether the argument is written in scripts.
+    pub fn is_visible(&self) -> bool {
+        !matches!(self.kind, ArgumentKind::Fixed { .. })
+    }
+
+    /// The largest value the argument can hold.
+    pub fn max(&self) -> u16 {
+        match (self.bits, &self.kind) {
+            (Some([_, width]), _) => (1 << width) - 1,
+            (None, ArgumentKind::U16) => 0xFFFF,
+            (None, _) => 0xFF,
+        }
+    }
+}
+
+/// Every command known to the decoder, by opcode.
+#[derive(Debug, Clone)]
+pub struct Opcodes(BTreeMap<u8, Opcode>);
+
+impl Default for Opcodes {
+    fn default() -> Self {
+        let opcodes = serde_json::from_str(include_str!("opcodes.json")).unwrap();
+        let mut default = Opcodes(BTreeMap::new());
+        default.extend(opcodes).unwrap();
+        default
+    }
+}
+
+impl Opcodes {
+    pub fn get(&self, opcode: u8) -> Option<&Opcode> {
+        self.0.get(&opcode)
+    }
+
+    /// Finds a command by its name in scripts.
+    pub fn find(&self, name: &str) -> Option<(u8, &Opcode)> {
+        self.0
+            .iter()
+            .find(|(_, opcode)| opcode.name == name)
+            .map(|(&code, opcode)| (code, opcode))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &Opcode)> {
+        self.0.iter().map(|(&code, opcode)| (code, opcode))
+    }
+
+    /// Adds or replaces opcodes, keyed by their value in decimal or hex.
+    pub fn extend(&mut self, opcodes: IndexMap<String, Opcode>) -> Result<(), Error> {
+        for (code, opcode) in opcodes {
+            let code = maybe_hex::<u8>(&code)
+                .map_err(|_| Error::InvalidTable(format!("invalid opcode `{code}`")))?;
+            validate(code, &opcode)
+                .map_err(|message| Error::InvalidTable(format!("opcode {code:#04X}: {message}")))?;
+            self.0.insert(code, opcode);
+        }
+
+        let mut names = BTreeMap::new();
+        for (code, opcode) in self.iter() {
+            if let Some(other) = names.insert(opcode.name.as_str(), code) {
+                return Err(Error::InvalidTable(format!(
+                    "`{}` is the name of opcodes {other:#04X} and {code:#04X}",
+                    opcode.name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The opcodes keyed the way [`Opcodes::extend`] reads them.
+    pub fn to_map(&self) -> IndexMap<String, Opcode> {
+        self.iter()
+            .map(|(code, opcode)| (format!("{code:#04X}"), opcode.clone()))
+            .collect()
+    }
+}
+
+fn validate(code: u8, opcode: &Opcode) -> Result<(), String> {
+    if code == END || code == NEW_LINE || PAGES.contains(&code) {
+        return Err("this opcode is built in and can't be redefined".into());
+    }
+    if opcode.name.is_empty() || !opcode.name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(format!("`{}` is not a valid command name", opcode.name));
+    }
+    if matches!(opcode.name.as_str(), "End" | "Raw") {
+        return Err(format!("`{}` is a built-in command name", opcode.name));
+    }
+
+    // end of the bits used in the current packed byte
+    let mut packed = None;
+    for arg in &opcode.args {
+        let (shift, width) = match arg.bits {
+            Some([shift, width]) => (shift, width),
+            None => {
+                packed = None;
+                continue;
+            }
+        };
+
+        if matches!(arg.kind, ArgumentKind::U16) {
+            return Err("a u16 argument can't be packed in bits".into());
+        }
+        if width == 0 || shift as u16 + width as u16 > 8 {
+            return Err(format!("bits [{shift}, {width}] don't fit in a byte"));
+        }
+        if shift != 0 && packed.is_none_or(|end| end > shift) {
+            return Err(format!(
+                "bits [{shift}, {width}] don't follow the bits of the previous argument"
+            ));
+        }
+        packed = Some(shift + width);
+    }
+
+    for arg in &opcode.args {
+        let too_big = |value: u8| value as u16 > arg.max();
+        match &arg.kind {
+            ArgumentKind::Enum { values } => {
+                if let Some((name, _)) = values.iter().find(|(_, value)| too_big(value.0)) {
+                    return Err(format!("the value of `{name}` doesn't fit in its bits"));
+                }
+            }
+            ArgumentKind::Fixed { value } if too_big(value.0) => {
+                return Err("the fixed value doesn't fit in its bits".into());
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
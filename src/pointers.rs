@@ -0,0 +1,86 @@
+use crate::error::Error;
+use crate::rom::Rom;
+use crate::rom::SnesAddress;
+
+/// Size of the pointers of a [`PointerTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerWidth {
+    /// 16-bit pointers into `bank`.
+    Short { bank: u8 },
+    /// 24-bit pointers.
+    Long,
+}
+
+impl PointerWidth {
+    pub fn size(self) -> usize {
+        match self {
+            PointerWidth::Short { .. } => 2,
+            PointerWidth::Long => 3,
+        }
+    }
+}
+
+/// A table of little-endian pointers in the ROM, like the one the game uses
+/// to find its dialogues.
+#[derive(Debug, Clone, Copy)]
+pub struct PointerTable {
+    /// Offset of the first pointer.
+    pub offset: usize,
+    pub count: usize,
+    pub width: PointerWidth,
+}
+
+impl PointerTable {
+    /// Offset of the pointer with ID `index`.
+    pub fn entry(&self, index: usize) -> usize {
+        self.offset + index * self.width.size()
+    }
+
+    /// Offset of the end of the table.
+    pub fn end(&self) -> usize {
+        self.entry(self.count)
+    }
+
+    /// Reads the pointer with ID `index`, or `None` if it is outside of `data`.
+    pub fn read(&self, data: &[u8], index: usize) -> Option<SnesAddress> {
+        let entry = self.entry(index);
+        let bytes = data.get(entry..entry + self.width.size())?;
+
+        let address = match self.width {
+            PointerWidth::Short { bank } => {
+                (bank as u32) << 16 | u16::from_le_bytes([bytes[0], bytes[1]]) as u32
+            }
+            PointerWidth::Long => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]),
+        };
+
+        Some(SnesAddress(address))
+    }
+
+    /// Makes the pointer with ID `index` point to the byte at `target` in the ROM.
+    pub fn write(&self, rom: &mut Rom, index: usize, target: usize) -> Result<(), Error> {
+        let entry = self.entry(index);
+        let unreachable = || Error::UnreachableTarget {
+            pointer: entry,
+            target,
+        };
+        let address = rom.address(target).ok_or_else(unreachable)?;
+
+        let bytes = match self.width {
+            PointerWidth::Short { bank } => {
+                // the game reads the pointer in `bank`, which has to map to the target
+                let address = SnesAddress((bank as u32) << 16 | address.addr() as u32);
+                if rom.offset(address) != Some(target) {
+                    return Err(unreachable());
+                }
+                address.addr().to_le_bytes().to_vec()
+            }
+            PointerWidth::Long => address.0.to_le_bytes()[..3].to_vec(),
+        };
+        rom.data
+            .get_mut(entry..entry + bytes.len())
+            .ok_or(Error::UnexpectedEnd { offset: entry })?
+            .copy_from_slice(&bytes);
+
+        Ok(())
+    }
+}
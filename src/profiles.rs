@@ -0,0 +1,162 @@
+use crate::crc32::crc32;
+use crate::error::Error;
+use crate::opcodes::Byte;
+use crate::rom::Position;
+use crate::rom::Rom;
+use clap_num::maybe_hex;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde::Deserializer;
+use std::fs;
+
+/// What is known about one revision of the game: how to recognize it, and
+/// where its dialogues and lists are.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Profile {
+    /// CRC32 of the ROM without its copier header.
+    #[serde(default, deserialize_with = "crc")]
+    pub crc32: Option<u32>,
+    /// Title of the cartridge header, as shown by [`Rom::title`].
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub version: Option<u8>,
+    /// Dialogue pointer tables, by name.
+    #[serde(default)]
+    pub dialogues: IndexMap<String, PointerTableLocation>,
+    /// Lists of strings, by name.
+    #[serde(default)]
+    pub lists: IndexMap<String, ListLocation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PointerTableLocation {
+    pub pointers: Position,
+    pub count: usize,
+    /// 16 or 24.
+    #[serde(default = "default_width")]
+    pub width: u8,
+    /// Bank of 16-bit pointers, by default the bank of the table.
+    #[serde(default)]
+    pub bank: Option<Byte>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListLocation {
+    pub start: Position,
+    pub end: Position,
+}
+
+pub(crate) fn default_width() -> u8 {
+    16
+}
+
+fn crc<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    maybe_hex::<u32>(&text)
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("`{text}` is not a CRC32")))
+}
+
+/// How a ROM matched a [`Profile`].
+#[derive(Debug, Clone, Copy)]
+pub enum Identification<'a> {
+    /// Same CRC32.
+    Exact(&'a str, &'a Profile),
+    /// Same title and version, but a different CRC32: most likely a modified ROM.
+    Header(&'a str, &'a Profile),
+    Unknown,
+}
+
+impl<'a> Identification<'a> {
+    pub fn profile(self) -> Option<(&'a str, &'a Profile)> {
+        match self {
+            Identification::Exact(name, profile) | Identification::Header(name, profile) => {
+                Some((name, profile))
+            }
+            Identification::Unknown => None,
+        }
+    }
+}
+
+/// Known revisions of the game, by name.
+#[derive(Debug, Clone)]
+pub struct Profiles(pub IndexMap<String, Profile>);
+
+/// The profiles of `profiles.json`, which only recognize the cartridge header
+/// of the game: they have no CRC32, dialogues or lists.
+impl Default for Profiles {
+    fn default() -> Self {
+        Profiles(parse(include_str!("profiles.json")).unwrap())
+    }
+}
+
+impl Profiles {
+    pub fn load(filename: &str) -> Result<Self, Error> {
+        Self::from_json(&fs::read_to_string(filename)?)
+    }
+
+    /// Reads profiles added to the default ones. They come first, and replace
+    /// the default profiles with the same name.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let mut profiles = parse(json)?;
+        for (name, profile) in Self::default().0 {
+            profiles.entry(name).or_insert(profile);
+        }
+
+        Ok(Profiles(profiles))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Finds the profile of `rom`, by its CRC32 or else by its title and version.
+    pub fn identify(&self, rom: &Rom) -> Identification<'_> {
+        let crc = crc32(&rom.data);
+        if let Some((name, profile)) = self
+            .0
+            .iter()
+            .find(|(_, profile)| profile.crc32 == Some(crc))
+        {
+            return Identification::Exact(name, profile);
+        }
+
+        let (title, version) = (rom.title(), rom.version());
+        let matches = |profile: &Profile| {
+            profile.title.is_some()
+                && profile.title == title
+                && profile
+                    .version
+                    .is_none_or(|expected| Some(expected) == version)
+        };
+        match self.0.iter().find(|(_, profile)| matches(profile)) {
+            Some((name, profile)) => Identification::Header(name, profile),
+            None => Identification::Unknown,
+        }
+    }
+}
+
+fn parse(json: &str) -> Result<IndexMap<String, Profile>, Error> {
+    let profiles: IndexMap<String, Profile> = serde_json::from_str(json)?;
+    for (name, profile) in &profiles {
+        if profile.crc32.is_none() && profile.title.is_none() {
+            return Err(Error::InvalidProfile(format!(
+                "`{name}` needs a crc32 or a title to be recognized"
+            )));
+        }
+        if let Some((table, _)) = profile
+            .dialogues
+            .iter()
+            .find(|(_, table)| !matches!(table.width, 16 | 24))
+        {
+            return Err(Error::InvalidProfile(format!(
+                "`{name}`: the pointers of `{table}` must be 16 or 24 bits"
+            )));
+        }
+    }
+
+    Ok(profiles)
+}
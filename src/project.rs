@@ -0,0 +1,146 @@
+use crate::error::Error;
+use crate::opcodes::Byte;
+use crate::profiles::ListLocation;
+use crate::profiles::PointerTableLocation;
+use crate::profiles::default_width;
+use crate::rom::Position;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Default name of the manifest of a project.
+pub const MANIFEST: &str = "fe3-text.json";
+
+/// Every text resource of a translation or hack, and where it goes in the ROM.
+///
+/// Paths are relative to the directory of the manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Project {
+    /// The unmodified ROM the resources are written into.
+    pub rom: PathBuf,
+    /// Character tables of every resource that doesn't have its own.
+    #[serde(default)]
+    pub table: Option<PathBuf>,
+    /// Regions that moved dialogues can be written to.
+    #[serde(default)]
+    pub free: Vec<Region>,
+    /// Also use the runs of `0xFF` or `0x00` bytes of the ROM as free space.
+    #[serde(default)]
+    pub detect_free: bool,
+    /// Shortest run used by `detect_free`.
+    #[serde(default = "default_min_free")]
+    pub min_free: usize,
+    #[serde(default)]
+    pub dialogues: IndexMap<String, DialogueResource>,
+    #[serde(default)]
+    pub lists: IndexMap<String, ListResource>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Region {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A pointer table and the directory of its `<ID>.txt` scripts.
+///
+/// The fields of the location are repeated, as unknown fields can't be
+/// rejected through `#[serde(flatten)]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DialogueResource {
+    pub pointers: Position,
+    pub count: usize,
+    #[serde(default = "default_width")]
+    pub width: u8,
+    #[serde(default)]
+    pub bank: Option<Byte>,
+    pub source: PathBuf,
+    #[serde(default)]
+    pub table: Option<PathBuf>,
+}
+
+/// A list of strings and its JSON file of labelled strings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListResource {
+    pub start: Position,
+    pub end: Position,
+    pub source: PathBuf,
+    #[serde(default)]
+    pub table: Option<PathBuf>,
+    /// One label per line for the strings when extracting them.
+    #[serde(default)]
+    pub labels: Option<PathBuf>,
+}
+
+fn default_min_free() -> usize {
+    0x40
+}
+
+impl DialogueResource {
+    pub fn location(&self) -> PointerTableLocation {
+        PointerTableLocation {
+            pointers: self.pointers,
+            count: self.count,
+            width: self.width,
+            bank: self.bank,
+        }
+    }
+}
+
+impl ListResource {
+    pub fn location(&self) -> ListLocation {
+        ListLocation {
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+impl Project {
+    /// Reads the manifest `filename`, making its paths relative to the
+    /// current directory.
+    pub fn load(filename: &Path) -> Result<Self, Error> {
+        let mut project = Self::from_json(&fs::read_to_string(filename)?)?;
+        let directory = filename.parent().unwrap_or(Path::new(""));
+
+        project.rom = directory.join(&project.rom);
+        project.table = project.table.map(|table| directory.join(table));
+        for dialogues in project.dialogues.values_mut() {
+            dialogues.source = directory.join(&dialogues.source);
+            dialogues.table = dialogues.table.take().map(|table| directory.join(table));
+        }
+        for list in project.lists.values_mut() {
+            list.source = directory.join(&list.source);
+            list.table = list.table.take().map(|table| directory.join(table));
+            list.labels = list.labels.take().map(|labels| directory.join(labels));
+        }
+
+        Ok(project)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let project: Project = serde_json::from_str(json)?;
+        if let Some((name, _)) = project
+            .dialogues
+            .iter()
+            .find(|(_, dialogues)| !matches!(dialogues.width, 16 | 24))
+        {
+            return Err(Error::InvalidManifest(format!(
+                "the pointers of `{name}` must be 16 or 24 bits"
+            )));
+        }
+
+        Ok(project)
+    }
+
+    /// Character tables of a resource with its own `table`.
+    pub fn table<'a>(&'a self, table: &'a Option<PathBuf>) -> Option<&'a Path> {
+        table.as_deref().or(self.table.as_deref())
+    }
+}
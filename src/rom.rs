@@ -0,0 +1,389 @@
+use clap_num::maybe_hex;
+use serde::Deserialize;
+use serde::Deserializer;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Size of the header some copiers add in front of the ROM.
+pub const COPIER_HEADER_SIZE: usize = 0x200;
+
+/// How the ROM is mapped on the SNES bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mapping {
+    LoRom,
+    HiRom,
+}
+
+impl Mapping {
+    /// File offset of the internal header of the cartridge.
+    fn header(self) -> usize {
+        match self {
+            Mapping::LoRom => 0x7FC0,
+            Mapping::HiRom => 0xFFC0,
+        }
+    }
+}
+
+impl FromStr for Mapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "lorom" => Ok(Mapping::LoRom),
+            "hirom" => Ok(Mapping::HiRom),
+            _ => Err(format!(
+                "unknown mapping `{s}`, expected `lorom` or `hirom`"
+            )),
+        }
+    }
+}
+
+/// An address on the SNES bus, written `$XX:YYYY`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnesAddress(pub u32);
+
+impl SnesAddress {
+    pub fn bank(self) -> u8 {
+        (self.0 >> 16) as u8
+    }
+
+    pub fn addr(self) -> u16 {
+        self.0 as u16
+    }
+}
+
+impl fmt::Display for SnesAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${:02X}:{:04X}", self.bank(), self.addr())
+    }
+}
+
+impl FromStr for SnesAddress {
+    type Err = String;
+
+    /// Parses `$XX:YYYY` or `$XXYYYY`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("`{s}` is not a SNES address like `$80:8000`");
+        let digits = s.strip_prefix('$').ok_or_else(invalid)?;
+        let (bank, addr) = match digits.split_once(':') {
+            Some((bank, addr)) if bank.len() <= 2 && addr.len() == 4 => (bank, addr),
+            None if digits.len() == 6 => digits.split_at(2),
+            _ => return Err(invalid()),
+        };
+        let bank = u8::from_str_radix(bank, 16).map_err(|_| invalid())?;
+        let addr = u16::from_str_radix(addr, 16).map_err(|_| invalid())?;
+
+        Ok(SnesAddress((bank as u32) << 16 | addr as u32))
+    }
+}
+
+/// A place in the ROM, given as an offset or as a SNES address starting with `$`.
+///
+/// Offsets don't count the copier header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    Offset(usize),
+    Address(SnesAddress),
+}
+
+impl FromStr for Position {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with('$') {
+            s.parse().map(Position::Address)
+        } else {
+            maybe_hex::<usize>(s).map(Position::Offset)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A ROM image without its copier header, if it had one.
+#[derive(Debug, Clone)]
+pub struct Rom {
+    /// The copier header, empty when the file has none.
+    pub copier_header: Vec<u8>,
+    pub data: Vec<u8>,
+    pub mapping: Mapping,
+}
+
+impl Rom {
+    /// Splits the copier header off `file` and detects the mapping, unless one is given.
+    pub fn new(mut file: Vec<u8>, mapping: Option<Mapping>) -> Self {
+        let data = if file.len() % 0x400 == COPIER_HEADER_SIZE {
+            file.split_off(COPIER_HEADER_SIZE)
+        } else {
+            std::mem::take(&mut file)
+        };
+        let mapping = mapping.unwrap_or_else(|| {
+            if header_score(&data, Mapping::HiRom) > header_score(&data, Mapping::LoRom) {
+                Mapping::HiRom
+            } else {
+                Mapping::LoRom
+            }
+        });
+
+        Rom {
+            copier_header: file,
+            data,
+            mapping,
+        }
+    }
+
+    /// Whether the cartridge header asks for FastROM, in which case the game
+    /// runs from the banks starting at `$80`.
+    fn is_fast(&self) -> bool {
+        self.data
+            .get(self.mapping.header() + 0x15)
+            .is_some_and(|mode| mode & 0x10 != 0)
+    }
+
+    /// The ROM as written to a file, with its copier header.
+    pub fn to_file(&self) -> Vec<u8> {
+        [self.copier_header.as_slice(), &self.data].concat()
+    }
+
+    /// Offsets of the cartridge header and the interrupt vectors.
+    pub fn header_range(&self) -> Range<usize> {
+        self.mapping.header()..self.mapping.header() + 0x40
+    }
+
+    /// The checksum and its complement, as stored in the cartridge header.
+    pub fn stored_checksum(&self) -> Option<(u16, u16)> {
+        let header = self.data.get(self.header_range())?;
+        let word = |offset: usize| u16::from_le_bytes([header[offset], header[offset + 1]]);
+        Some((word(0x1E), word(0x1C)))
+    }
+
+    /// The checksum the cartridge header should have: the sum of every byte,
+    /// counting the checksum as `0x0000` and its complement as `0xFFFF`.
+    ///
+    /// A ROM whose size isn't a power of two is summed as the console sees it,
+    /// with its last part mirrored.
+    pub fn checksum(&self) -> u16 {
+        let mut data = self.data.clone();
+        let header = self.mapping.header();
+        if let Some(bytes) = data.get_mut(header + 0x1C..header + 0x20) {
+            bytes.copy_from_slice(&[0xFF, 0xFF, 0x00, 0x00]);
+        }
+        mirrored_sum(&data) as u16
+    }
+
+    /// Writes the correct checksum and complement to the cartridge header.
+    pub fn fix_checksum(&mut self) {
+        let checksum = self.checksum();
+        let header = self.mapping.header();
+        if let Some(bytes) = self.data.get_mut(header + 0x1C..header + 0x20) {
+            bytes[..2].copy_from_slice(&(!checksum).to_le_bytes());
+            bytes[2..].copy_from_slice(&checksum.to_le_bytes());
+        }
+    }
+
+    /// Size of the part of the ROM mapped in one bank.
+    pub fn bank_size(&self) -> usize {
+        match self.mapping {
+            Mapping::LoRom => 0x8000,
+            Mapping::HiRom => 0x10000,
+        }
+    }
+
+    /// Offsets of the bytes mapped in `bank`.
+    pub fn bank_range(&self, bank: u8) -> Option<Range<usize>> {
+        let address = |addr: u16| SnesAddress((bank as u32) << 16 | addr as u32);
+        let start = self
+            .offset(address(0x0000))
+            .or_else(|| self.offset(address(0x8000)))?;
+        let end = self
+            .offset(address(0xFFFF))
+            .map_or(self.data.len(), |end| end + 1);
+        Some(start..end)
+    }
+
+    /// Offset in [`Rom::data`] of `position`, if it is in the ROM.
+    pub fn resolve(&self, position: Position) -> Option<usize> {
+        match position {
+            Position::Offset(offset) => (offset <= self.data.len()).then_some(offset),
+            Position::Address(address) => self.offset(address),
+        }
+    }
+
+    /// The game title in the cartridge header, without its padding.
+    ///
+    /// Japanese titles use half-width katakana, which are shown as `?`.
+    pub fn title(&self) -> Option<String> {
+        let header = self.data.get(self.header_range())?;
+        let title = header[..21]
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '?'
+                }
+            })
+            .collect::<String>();
+        Some(title.trim_end().to_string())
+    }
+
+    /// The version byte of the cartridge header, 0 for the first release.
+    pub fn version(&self) -> Option<u8> {
+        self.data.get(self.mapping.header() + 0x1B).copied()
+    }
+
+    /// Offset in [`Rom::data`] of the byte mapped at `address`.
+    pub fn offset(&self, address: SnesAddress) -> Option<usize> {
+        let (bank, addr) = (address.bank(), address.addr() as usize);
+        let offset = match self.mapping {
+            Mapping::LoRom => {
+                if addr < 0x8000 || matches!(bank, 0x7E | 0x7F) {
+                    return None;
+                }
+                ((bank & 0x7F) as usize) << 15 | (addr & 0x7FFF)
+            }
+            Mapping::HiRom => {
+                if matches!(bank, 0x7E | 0x7F) || (bank & 0x40 == 0 && addr < 0x8000) {
+                    return None;
+                }
+                ((bank & 0x3F) as usize) << 16 | addr
+            }
+        };
+
+        (offset < self.data.len()).then_some(offset)
+    }
+
+    /// The address the game reads the byte at `offset` from.
+    pub fn address(&self, offset: usize) -> Option<SnesAddress> {
+        if offset >= self.data.len() {
+            return None;
+        }
+
+        let address = match self.mapping {
+            Mapping::LoRom => {
+                let mut bank = (offset >> 15) as u32;
+                if self.is_fast() || bank >= 0x7E {
+                    bank |= 0x80;
+                }
+                bank << 16 | (offset as u32 & 0x7FFF) | 0x8000
+            }
+            Mapping::HiRom => ((offset >> 16) as u32 | 0xC0) << 16 | (offset as u32 & 0xFFFF),
+        };
+
+        Some(SnesAddress(address))
+    }
+}
+
+/// The sum of the bytes of `data` repeated up to the next power of two, the
+/// way a cartridge of that size mirrors its last part.
+fn mirrored_sum(data: &[u8]) -> u32 {
+    if data.is_empty() {
+        return 0;
+    }
+
+    let base = 1 << data.len().ilog2();
+    let (first, rest) = data.split_at(base);
+    let sum = first
+        .iter()
+        .fold(0u32, |sum, &byte| sum.wrapping_add(byte as u32));
+    if rest.is_empty() {
+        return sum;
+    }
+    let repeats = (base / rest.len().next_power_of_two()) as u32;
+    sum.wrapping_add(mirrored_sum(rest).wrapping_mul(repeats))
+}
+
+/// How much the data at the header location of `mapping` looks like a cartridge header.
+fn header_score(data: &[u8], mapping: Mapping) -> u32 {
+    let Some(header) = data.get(mapping.header()..mapping.header() + 0x40) else {
+        return 0;
+    };
+    let word = |offset: usize| u16::from_le_bytes([header[offset], header[offset + 1]]);
+    let mut score = 0;
+
+    if word(0x1C) ^ word(0x1E) == 0xFFFF {
+        score += 4;
+    }
+    let expected = match mapping {
+        Mapping::LoRom => 0,
+        Mapping::HiRom => 1,
+    };
+    if header[0x15] & 0xE0 == 0x20 && header[0x15] & 0x0F == expected {
+        score += 2;
+    }
+    // reset vector
+    if word(0x3C) >= 0x8000 {
+        score += 1;
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(s: &str) -> SnesAddress {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn mirrored_sum_of_3_mib() {
+        // the last MiB is seen twice, to fill the 4 MiB of the address space
+        let mut data = vec![1; 0x300000];
+        data[0x200000..].fill(2);
+        assert_eq!(mirrored_sum(&data), 0x200000 + 2 * 2 * 0x100000);
+        assert_eq!(mirrored_sum(&data[..0x200000]), 0x200000);
+        assert_eq!(mirrored_sum(&[]), 0);
+    }
+
+    #[test]
+    fn checksum() {
+        let mut rom = Rom::new(vec![1; 0x300000], Some(Mapping::LoRom));
+        rom.fix_checksum();
+        let (checksum, complement) = rom.stored_checksum().unwrap();
+        assert_eq!(checksum, rom.checksum());
+        assert_eq!(checksum ^ complement, 0xFFFF);
+    }
+
+    #[test]
+    fn lorom() {
+        let mut rom = Rom::new(vec![0; 0x300000], Some(Mapping::LoRom));
+        assert_eq!(rom.offset(address("$00:8000")), Some(0));
+        assert_eq!(rom.offset(address("$80:8000")), Some(0));
+        assert_eq!(rom.offset(address("$82:9234")), Some(0x11234));
+        assert_eq!(rom.offset(address("$82:1234")), None);
+        assert_eq!(rom.offset(address("$7E:8000")), None);
+        assert_eq!(rom.offset(address("$E0:8000")), None);
+        assert_eq!(rom.address(0x11234), Some(address("$02:9234")));
+        assert_eq!(rom.address(0x300000), None);
+
+        rom.data[Mapping::LoRom.header() + 0x15] = 0x30;
+        assert_eq!(rom.address(0x11234), Some(address("$82:9234")));
+        for offset in (0..rom.data.len()).step_by(0x1357) {
+            assert_eq!(rom.offset(rom.address(offset).unwrap()), Some(offset));
+        }
+    }
+
+    #[test]
+    fn hirom() {
+        let rom = Rom::new(vec![0; 0x300000], Some(Mapping::HiRom));
+        assert_eq!(rom.offset(address("$C0:0000")), Some(0));
+        assert_eq!(rom.offset(address("$42:1234")), Some(0x21234));
+        assert_eq!(rom.offset(address("$02:9234")), Some(0x29234));
+        assert_eq!(rom.offset(address("$02:1234")), None);
+        assert_eq!(rom.offset(address("$7E:0000")), None);
+        assert_eq!(rom.offset(address("$F0:0000")), None);
+        assert_eq!(rom.address(0x21234), Some(address("$C2:1234")));
+        for offset in (0..rom.data.len()).step_by(0x1357) {
+            assert_eq!(rom.offset(rom.address(offset).unwrap()), Some(offset));
+        }
+    }
+}
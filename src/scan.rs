@@ -0,0 +1,150 @@
+use crate::dialogue::DialogueCommand;
+use crate::dialogue::decode_dialogue_spans;
+use crate::tables::Tables;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScanOptions {
+    /// Shortest script reported, in bytes.
+    pub min_length: usize,
+    /// Longest script looked for, in bytes.
+    pub max_length: usize,
+    /// Lowest score reported, out of 100.
+    pub min_score: u8,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            min_length: 16,
+            max_length: 0x2000,
+            min_score: 50,
+        }
+    }
+}
+
+/// A region of the ROM that decodes as a dialogue script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub range: Range<usize>,
+    /// How much the region looks like a real script, out of 100.
+    pub score: u8,
+}
+
+/// Looks for dialogue scripts in `data`: regions that decode without errors
+/// up to an `[End]` and look like text.
+///
+/// Candidates don't overlap. The score favours scripts made mostly of text
+/// with some commands, starting with `[StartDialogue]`, following the `[End]`
+/// of another script and without unidentified glyphs.
+pub fn scan_dialogue(data: &[u8], tables: &Tables, options: &ScanOptions) -> Vec<Candidate> {
+    // the name is the only way to know the command from a user's opcode table
+    let start_dialogue = tables.opcodes.find("StartDialogue").map(|(code, _)| code);
+
+    // position of the first `00 00`, the shortest possible [End], from each offset
+    let mut next_end = vec![data.len(); data.len() + 1];
+    for i in (0..data.len().saturating_sub(1)).rev() {
+        next_end[i] = if data[i] == 0 && data[i + 1] == 0 {
+            i
+        } else {
+            next_end[i + 1]
+        };
+    }
+
+    let mut candidates = vec![];
+    let mut offset = 0;
+    while offset < data.len() && next_end[offset] < data.len() {
+        // nothing starting before this can end within `max_length`
+        let earliest = (next_end[offset] + 2).saturating_sub(options.max_length);
+        if earliest > offset {
+            offset = earliest;
+            continue;
+        }
+
+        let end = (offset + options.max_length).min(data.len());
+        match candidate(&data[..end], offset, tables, start_dialogue, options) {
+            Some(candidate) => {
+                offset = candidate.range.end;
+                candidates.push(candidate);
+            }
+            None => offset += 1,
+        }
+    }
+
+    candidates
+}
+
+fn candidate(
+    data: &[u8],
+    offset: usize,
+    tables: &Tables,
+    start_dialogue: Option<u8>,
+    options: &ScanOptions,
+) -> Option<Candidate> {
+    let is_start = |command: &DialogueCommand| match command {
+        DialogueCommand::Command { opcode, .. } => Some(*opcode) == start_dialogue,
+        _ => false,
+    };
+
+    let spans = decode_dialogue_spans(&data[offset..], tables).ok()?;
+    // bytes before a [StartDialogue] are most likely not part of the script
+    let first = spans
+        .iter()
+        .position(|(_, command)| is_start(command))
+        .unwrap_or(0);
+    let spans = &spans[first..];
+
+    let begin = offset + spans[0].0.start;
+    let end = offset + spans.last()?.0.end;
+    let length = end - begin;
+    let text = spans
+        .iter()
+        .filter(|(_, command)| matches!(command, DialogueCommand::Text(_)))
+        .map(|(span, _)| span.len())
+        .sum::<usize>();
+    if length < options.min_length || text == 0 {
+        return None;
+    }
+
+    // padding or graphics rather than text
+    if data[begin..end]
+        .windows(16)
+        .any(|run| run.iter().all(|&b| b == run[0]))
+    {
+        return None;
+    }
+
+    let mut score = 30 * text / length;
+    if is_start(&spans[0].1) {
+        score += 30;
+    }
+    if begin == 0 || data[..begin].ends_with(&[0x00, 0x00]) {
+        score += 15;
+    }
+    // random bytes can decode as a long text, but rarely contain a command
+    let commands = spans
+        .iter()
+        .filter(|(_, command)| {
+            matches!(
+                command,
+                DialogueCommand::NewLine | DialogueCommand::Command { .. }
+            )
+        })
+        .count();
+    if commands > 0 {
+        score += 15;
+    }
+    if length >= 64 {
+        score += 10;
+    }
+    let unknown = spans
+        .iter()
+        .filter(|(_, command)| matches!(command, DialogueCommand::UnknownGlyph { .. }))
+        .count();
+    let score = score.saturating_sub(10 * unknown).min(100) as u8;
+
+    (score >= options.min_score).then_some(Candidate {
+        range: begin..end,
+        score,
+    })
+}
@@ -0,0 +1,403 @@
+use crate::dialogue::DialogueCommand;
+use crate::error::Error;
+use crate::error::Location;
+use crate::opcodes::ArgumentKind;
+use crate::opcodes::PAGES;
+use crate::tables::Tables;
+use clap_num::maybe_hex;
+
+/// Byte order mark some editors write at the start of UTF-8 files.
+pub(crate) const BOM: char = '\u{FEFF}';
+
+/// Turns decoded commands into the textual script format read by [`parse_dialogue`].
+///
+/// With `highlight`, unknown portraits and glyphs are wrapped in ANSI colours.
+/// Glyphs of `[`, `{`, `\` or a line break are written as `{page:code}`.
+pub fn format_dialogue(commands: &[DialogueCommand], tables: &Tables, highlight: bool) -> String {
+    let (warn, reset) = if highlight {
+        ("\x1b[93m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+
+    let mut script = String::new();
+    for command in commands {
+        match command {
+            DialogueCommand::Text(text) => {
+                for c in text.chars() {
+                    // glyphs that would be read as syntax are written as escapes
+                    match tables.find_glyph(c) {
+                        Some((page, code)) if matches!(c, '[' | '{' | '\\' | '\n') => {
+                            script.push_str(&format!("{{{:02X}:{code:02X}}}", page + PAGES.start()))
+                        }
+                        _ => script.push(c),
+                    }
+                }
+            }
+            DialogueCommand::UnknownGlyph { page, code } => script.push_str(&format!(
+                "{warn}{{{:02X}:{code:02X}}}{reset}",
+                page + PAGES.start()
+            )),
+            DialogueCommand::End => script.push_str("[End]\n"),
+            DialogueCommand::NewLine => script.push_str("\\n"),
+            DialogueCommand::Raw(bytes) => {
+                let bytes = bytes
+                    .iter()
+                    .map(|byte| format!("{byte:#04X}"))
+                    .collect::<Vec<_>>();
+                script.push_str(&format!("{warn}[Raw({})]{reset}", bytes.join(", ")));
+            }
+            DialogueCommand::Command { opcode, args } => {
+                let Some(spec) = tables.opcodes.get(*opcode) else {
+                    script.push_str(&format!("[{opcode:#04X}]"));
+                    continue;
+                };
+
+                let mut unnamed = false;
+                let args = spec
+                    .visible_args()
+                    .zip(args)
+                    .map(|(arg, &value)| match &arg.kind {
+                        ArgumentKind::Enum { .. } => arg
+                            .value_name(value)
+                            .map_or(value.to_string(), str::to_string),
+                        ArgumentKind::Portrait => match tables.portrait_name(value as u8) {
+                            Some(name) => name.to_string(),
+                            None => {
+                                unnamed = true;
+                                format!("#{value}")
+                            }
+                        },
+                        _ if arg.hex => format!("{value:#X}"),
+                        _ => value.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+
+                let (warn, reset) = if unnamed { (warn, reset) } else { ("", "") };
+                script.push_str(warn);
+                script.push('[');
+                script.push_str(&spec.name);
+                if !args.is_empty() {
+                    script.push_str(&format!("({})", args.join(", ")));
+                }
+                script.push(']');
+                if !spec.inline {
+                    script.push('\n');
+                }
+                script.push_str(reset);
+            }
+        }
+    }
+
+    script
+}
+
+/// Parses a textual dialogue script back into commands.
+///
+/// Parsing goes on after an error, so that every error of the script is returned.
+/// Lines can end with `\r\n`, and a leading byte order mark is ignored. The
+/// script must end with `[End]`, which the game stops reading at.
+pub fn parse_dialogue(script: &str, tables: &Tables) -> Result<Vec<DialogueCommand>, Vec<Error>> {
+    let script = script.strip_prefix(BOM).unwrap_or(script);
+    let mut commands = vec![];
+    let mut errors = vec![];
+    let mut text = String::new();
+    let mut chars = Chars::new(script);
+    let mut ended = false;
+
+    while let Some((location, c)) = chars.next() {
+        if ended && c != '\n' {
+            errors.push(Error::Syntax {
+                location,
+                message: "nothing can follow `[End]`".into(),
+            });
+            return Err(errors);
+        }
+        let command = match c {
+            '\n' => continue,
+            '\\' if chars.peek() == Some('n') => {
+                chars.next();
+                DialogueCommand::NewLine
+            }
+            '{' => {
+                let mut escape = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, c)) if c != '\n' => escape.push(c),
+                        _ => {
+                            errors.push(Error::Syntax {
+                                location,
+                                message: "`{` is never closed".into(),
+                            });
+                            return Err(errors);
+                        }
+                    }
+                }
+                match glyph(&escape) {
+                    Some(glyph) => glyph,
+                    None => {
+                        errors.push(Error::Syntax {
+                            location,
+                            message: format!(
+                                "`{{{escape}}}` is not a glyph like `{{12:0B}}`, with a page from 11 to 14"
+                            ),
+                        });
+                        continue;
+                    }
+                }
+            }
+            '[' => {
+                let mut inner = vec![];
+                let mut depth = 0;
+                loop {
+                    let Some((location, c)) = chars.next() else {
+                        errors.push(Error::Syntax {
+                            location,
+                            message: "`[` is never closed".into(),
+                        });
+                        return Err(errors);
+                    };
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        ']' if depth == 0 => break,
+                        _ => {}
+                    }
+                    inner.push((location, c));
+                }
+                match parse_command(location, &inner, tables) {
+                    Ok(command) => command,
+                    Err(error) => {
+                        errors.push(error);
+                        continue;
+                    }
+                }
+            }
+            c => {
+                if tables.find_glyph(c).is_none() {
+                    errors.push(Error::UnmappedCharacter {
+                        location: Some(location),
+                        character: c,
+                    });
+                }
+                text.push(c);
+                continue;
+            }
+        };
+
+        if !text.is_empty() {
+            commands.push(DialogueCommand::Text(std::mem::take(&mut text)));
+        }
+        ended = command == DialogueCommand::End;
+        commands.push(command);
+    }
+
+    if !ended {
+        errors.push(Error::Syntax {
+            location: chars.location,
+            message: "expected `[End]` at the end of the script".into(),
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(commands)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Characters of a script along with their location, `\r\n` being read as
+/// `\n`.
+struct Chars<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    location: Location,
+}
+
+impl<'a> Chars<'a> {
+    fn new(script: &'a str) -> Self {
+        Self {
+            chars: script.chars().peekable(),
+            location: Location { line: 1, column: 1 },
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+}
+
+impl Iterator for Chars<'_> {
+    type Item = (Location, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut c = self.chars.next()?;
+        if c == '\r' && self.peek() == Some('\n') {
+            c = self.chars.next()?;
+        }
+        let location = self.location;
+        if c == '\n' {
+            self.location.line += 1;
+            self.location.column = 1;
+        } else {
+            self.location.column += 1;
+        }
+        Some((location, c))
+    }
+}
+
+/// A command argument, trimmed, with the location of its first character.
+struct Argument {
+    location: Location,
+    value: String,
+}
+
+/// Parses the inside of `[...]`. `bracket` is the location of the `[`.
+fn parse_command(
+    bracket: Location,
+    command: &[(Location, char)],
+    tables: &Tables,
+) -> Result<DialogueCommand, Error> {
+    let name_end = command
+        .iter()
+        .position(|&(_, c)| c == '(')
+        .unwrap_or(command.len());
+    let name = command[..name_end]
+        .iter()
+        .map(|&(_, c)| c)
+        .collect::<String>();
+    let location = command.first().map_or(bracket, |&(location, _)| location);
+
+    let mut args = vec![];
+    if let Some(&(open, _)) = command.get(name_end) {
+        let Some(((close, _), inner)) = command[name_end + 1..].split_last() else {
+            return Err(Error::Syntax {
+                location: open,
+                message: "`(` is never closed".into(),
+            });
+        };
+        if command.last().unwrap().1 != ')' {
+            return Err(Error::Syntax {
+                location: *close,
+                message: "expected `]` after `)`".into(),
+            });
+        }
+
+        let mut depth = 0;
+        let mut current = vec![];
+        for &(location, c) in inner {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => {
+                    args.push(argument(&current, location));
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            }
+            current.push((location, c));
+        }
+        if !inner.is_empty() {
+            args.push(argument(&current, *close));
+        }
+    }
+
+    if name == "End" && args.is_empty() {
+        return Ok(DialogueCommand::End);
+    }
+    if name == "Raw" && !args.is_empty() {
+        return args
+            .iter()
+            .map(|arg| {
+                maybe_hex::<u8>(&arg.value).map_err(|_| Error::InvalidArgument {
+                    location: arg.location,
+                    name: name.clone(),
+                    argument: "byte".into(),
+                    value: arg.value.clone(),
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(DialogueCommand::Raw);
+    }
+    let Some((opcode, spec)) = tables.opcodes.find(&name) else {
+        return Err(Error::UnknownCommand { location, name });
+    };
+    let expected = spec.visible_args().count();
+    if args.len() != expected {
+        return Err(Error::ArgumentCount {
+            location,
+            name,
+            expected,
+            found: args.len(),
+        });
+    }
+
+    let values = spec
+        .visible_args()
+        .zip(&args)
+        .map(|(spec, arg)| {
+            let invalid = || Error::InvalidArgument {
+                location: arg.location,
+                name: name.clone(),
+                argument: match spec.name.as_str() {
+                    "" => "argument".into(),
+                    argument => argument.into(),
+                },
+                value: arg.value.clone(),
+            };
+
+            match &spec.kind {
+                ArgumentKind::Enum { values } => values
+                    .get(&arg.value)
+                    .map(|value| value.0 as u16)
+                    .ok_or_else(invalid),
+                ArgumentKind::Portrait => tables
+                    .find_portrait(&arg.value)
+                    .map(u16::from)
+                    .ok_or_else(|| Error::UnknownPortrait {
+                        location: arg.location,
+                        name: arg.value.clone(),
+                    }),
+                _ => maybe_hex::<u16>(&arg.value)
+                    .ok()
+                    .filter(|&value| value <= spec.max())
+                    .ok_or_else(invalid),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(DialogueCommand::Command {
+        opcode,
+        args: values,
+    })
+}
+
+/// Parses the inside of a `{12:0B}` glyph escape: a page switch and a code.
+fn glyph(escape: &str) -> Option<DialogueCommand> {
+    let byte = |digits: &str| {
+        let valid = (1..=2).contains(&digits.len())
+            && digits.chars().all(|digit| digit.is_ascii_hexdigit());
+        valid.then(|| u8::from_str_radix(digits, 16).unwrap())
+    };
+    let (page, code) = escape.split_once(':')?;
+    let (page, code) = (byte(page)?, byte(code)?);
+    (PAGES.contains(&page) && code != 0).then_some(DialogueCommand::UnknownGlyph {
+        page: page - PAGES.start(),
+        code,
+    })
+}
+
+/// Trims an argument. An empty argument is located at `end`.
+fn argument(chars: &[(Location, char)], end: Location) -> Argument {
+    let trimmed = chars
+        .iter()
+        .skip_while(|(_, c)| c.is_whitespace())
+        .collect::<Vec<_>>();
+    let value = trimmed.iter().map(|&&(_, c)| c).collect::<String>();
+
+    Argument {
+        location: trimmed.first().map_or(end, |&&(location, _)| location),
+        value: value.trim_end().to_string(),
+    }
+}
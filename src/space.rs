@@ -0,0 +1,111 @@
+use std::ops::Range;
+
+/// Regions of the ROM that can be overwritten, sorted and without overlaps.
+#[derive(Debug, Clone, Default)]
+pub struct FreeSpace(Vec<Range<usize>>);
+
+impl FreeSpace {
+    /// Finds runs of at least `min_length` `0xFF` or `0x00` bytes, the padding
+    /// the game leaves at the end of its banks.
+    ///
+    /// A run of `0x00` may start with the `[End]` of a dialogue, so its first
+    /// two bytes are left alone.
+    pub fn detect(data: &[u8], min_length: usize) -> Self {
+        let mut space = FreeSpace::default();
+        let mut start = 0;
+
+        while start < data.len() {
+            let byte = data[start];
+            let end = data[start..]
+                .iter()
+                .position(|&b| b != byte)
+                .map_or(data.len(), |length| start + length);
+
+            let run = match byte {
+                0x00 => start + 2..end,
+                0xFF => start..end,
+                _ => end..end,
+            };
+            if run.len() >= min_length {
+                space.free(run);
+            }
+            start = end;
+        }
+
+        space
+    }
+
+    pub fn regions(&self) -> &[Range<usize>] {
+        &self.0
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.iter().map(Range::len).sum()
+    }
+
+    /// Marks `range` as free, merging it with the regions around it.
+    pub fn free(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mut merged = range;
+        self.0.retain(|region| {
+            let overlaps = region.start <= merged.end && merged.start <= region.end;
+            if overlaps {
+                merged = merged.start.min(region.start)..merged.end.max(region.end);
+            }
+            !overlaps
+        });
+        let index = self.0.partition_point(|region| region.start < merged.start);
+        self.0.insert(index, merged);
+    }
+
+    /// Removes `range` from the free space.
+    pub fn reserve(&mut self, range: Range<usize>) {
+        self.0 = self
+            .0
+            .iter()
+            .flat_map(|region| {
+                [
+                    region.start..region.end.min(range.start),
+                    region.start.max(range.end)..region.end,
+                ]
+            })
+            .filter(|region| !region.is_empty())
+            .collect();
+    }
+
+    /// Takes `length` bytes of free space inside `within` and not crossing a
+    /// multiple of `bank_size`, and returns the offset of the first one.
+    pub fn allocate(
+        &mut self,
+        length: usize,
+        within: Range<usize>,
+        bank_size: usize,
+    ) -> Option<usize> {
+        if length > bank_size {
+            return None;
+        }
+        let (index, start) = self.0.iter().enumerate().find_map(|(index, region)| {
+            let start = region.start.max(within.start);
+            let end = region.end.min(within.end);
+            // move to the next bank if the data would cross into it
+            let bank_end = (start / bank_size + 1) * bank_size;
+            let start = if start + length > bank_end {
+                bank_end
+            } else {
+                start
+            };
+            (start + length <= end).then_some((index, start))
+        })?;
+
+        let region = self.0.remove(index);
+        let after = start + length..region.end;
+        self.0.insert(index, region.start..start);
+        self.0.insert(index + 1, after);
+        self.0.retain(|region| !region.is_empty());
+
+        Some(start)
+    }
+}
@@ -0,0 +1,577 @@
+use crate::error::Error;
+use crate::list::NEW_LINE;
+use crate::opcodes::Opcode;
+use crate::opcodes::Opcodes;
+use clap_num::maybe_hex;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde::Serialize;
+use std::fs::read_to_string;
+
+/// Characters of the two fonts: `list` is indexed by the u16 codes of string
+/// lists, `dialogue` by the page (0-3) then the byte code of dialogue scripts.
+/// `'_'` marks a glyph that hasn't been identified yet.
+///
+/// `portraits` holds the names (and aliases) of each of the 256 portraits, and
+/// `opcodes` describes the commands of dialogue scripts.
+#[derive(Debug, Clone)]
+pub struct Tables {
+    pub list: Vec<char>,
+    pub dialogue: Vec<Vec<char>>,
+    pub portraits: Vec<Vec<String>>,
+    pub opcodes: Opcodes,
+}
+
+/// On-disk format of [`Tables`]: each table is a list of rows (of any length)
+/// that are concatenated. A missing table keeps its default.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TablesFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    list: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dialogue: Option<Vec<Vec<String>>>,
+    /// Portrait ID (decimal or hex) to name or list of aliases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    portraits: Option<IndexMap<String, Names>>,
+    /// Opcode (decimal or hex) to description, added to or replacing the default ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    opcodes: Option<IndexMap<String, Opcode>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Names {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Default for Tables {
+    fn default() -> Self {
+        Self {
+            list: LISTS_CHARACTERS.to_vec(),
+            dialogue: DIALOGUES_CHARACTERS
+                .iter()
+                .map(|page| page.to_vec())
+                .collect(),
+            portraits: PORTRAITS
+                .iter()
+                .map(|names| names.iter().map(|name| name.to_string()).collect())
+                .collect(),
+            opcodes: Opcodes::default(),
+        }
+    }
+}
+
+impl Tables {
+    /// Loads tables from a JSON file.
+    pub fn load(filename: &str) -> Result<Self, Error> {
+        Self::from_json(&read_to_string(filename)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let file: TablesFile = serde_json::from_str(json)?;
+        let mut tables = Self::default();
+
+        if let Some(list) = file.list {
+            tables.list = list.concat().chars().collect();
+            if tables.list.len() >= NEW_LINE as usize {
+                return Err(Error::InvalidTable(format!(
+                    "the list table has {} characters but codes from 0xFFFD are control codes",
+                    tables.list.len()
+                )));
+            }
+        }
+
+        if let Some(dialogue) = file.dialogue {
+            if dialogue.len() != 4 {
+                return Err(Error::InvalidTable(format!(
+                    "the dialogue table needs 4 pages, not {}",
+                    dialogue.len()
+                )));
+            }
+
+            tables.dialogue = dialogue
+                .iter()
+                .map(|page| page.concat().chars().collect::<Vec<_>>())
+                .collect();
+            for (page, characters) in tables.dialogue.iter().enumerate() {
+                if characters.len() != 256 {
+                    return Err(Error::InvalidTable(format!(
+                        "page {:02X} of the dialogue table has {} characters instead of 256",
+                        page + 0x11,
+                        characters.len()
+                    )));
+                }
+            }
+        }
+
+        if let Some(portraits) = file.portraits {
+            tables.portraits = vec![vec![]; 256];
+            for (id, names) in portraits {
+                let portrait = maybe_hex::<u8>(&id)
+                    .map_err(|_| Error::InvalidTable(format!("invalid portrait ID `{id}`")))?;
+                let names = match names {
+                    Names::One(name) => vec![name],
+                    Names::Many(names) => names,
+                };
+
+                for name in &names {
+                    if name.is_empty() || name.starts_with('#') || name.contains([',', '[', ']']) {
+                        return Err(Error::InvalidTable(format!(
+                            "`{name}` can't be used as a portrait name"
+                        )));
+                    }
+                    if let Some(other) = tables.find_portrait(name) {
+                        return Err(Error::InvalidTable(format!(
+                            "`{name}` is used by portraits {other} and {portrait}"
+                        )));
+                    }
+                }
+                tables.portraits[portrait as usize].extend(names);
+            }
+        }
+
+        if let Some(opcodes) = file.opcodes {
+            tables.opcodes.extend(opcodes)?;
+        }
+
+        Ok(tables)
+    }
+
+    /// Formats the tables in the format read by [`Tables::from_json`], 16 characters per row.
+    pub fn to_json(&self) -> String {
+        let rows = |characters: &[char]| {
+            characters
+                .chunks(16)
+                .map(String::from_iter)
+                .collect::<Vec<_>>()
+        };
+        let portraits = self
+            .portraits
+            .iter()
+            .enumerate()
+            .filter(|(_, names)| !names.is_empty())
+            .map(|(id, names)| {
+                let names = match names.as_slice() {
+                    [name] => Names::One(name.clone()),
+                    names => Names::Many(names.to_vec()),
+                };
+                (id.to_string(), names)
+            })
+            .collect();
+        let file = TablesFile {
+            list: Some(rows(&self.list)),
+            dialogue: Some(self.dialogue.iter().map(|page| rows(page)).collect()),
+            portraits: Some(portraits),
+            opcodes: Some(self.opcodes.to_map()),
+        };
+
+        serde_json::to_string_pretty(&file).unwrap()
+    }
+
+    /// The name a portrait is decompiled to, `None` if it has none.
+    pub fn portrait_name(&self, portrait: u8) -> Option<&str> {
+        self.portraits[portrait as usize]
+            .first()
+            .map(String::as_str)
+    }
+
+    /// Finds a portrait by any of its names, or by its ID written as `#148` or `#0x94`.
+    pub fn find_portrait(&self, name: &str) -> Option<u8> {
+        if let Some(id) = name.strip_prefix('#') {
+            return maybe_hex::<u8>(id).ok();
+        }
+
+        self.portraits
+            .iter()
+            .position(|names| names.iter().any(|n| n == name))
+            .map(|id| id as u8)
+    }
+
+    /// Finds the code of a character in the list font.
+    pub fn find_list_character(&self, c: char) -> Option<u16> {
+        if c == '_' {
+            return None;
+        }
+
+        self.list
+            .iter()
+            .position(|&r| r == c)
+            .map(|index| index as u16)
+    }
+
+    /// Finds the page (0-3) and code of a character in the dialogue font.
+    pub fn find_glyph(&self, c: char) -> Option<(u8, u8)> {
+        if c == '_' {
+            return None;
+        }
+
+        self.dialogue
+            .iter()
+            .enumerate()
+            .find_map(|(page, characters)| {
+                // code 0 is the command prefix, never a character
+                let code = characters.iter().skip(1).position(|&r| r == c)? + 1;
+                Some((page as u8, code as u8))
+            })
+    }
+}
+
+#[rustfmt::skip]
+pub const LISTS_CHARACTERS: [char; 283] = [
+    /*  00 */ 'あ', 'い', 'う', 'え', 'お', 'か', 'き', 'く', 'け', 'こ', 'さ', 'し', 'す', 'せ', 'そ', 'た',
+    /*  10 */ 'ち', 'つ', 'て', 'と', 'な', 'に', 'ぬ', 'ね', 'の', 'は', 'ひ', 'ふ', 'へ', 'ほ', 'ま', 'み',
+    /*  20 */ 'む', 'め', 'も', 'や', 'ゆ', 'よ', 'ら', 'り', 'る', 'れ', 'ろ', 'わ', 'を', 'ん', 'が', 'ぎ',
+    /*  30 */ 'ぐ', 'げ', 'ご', 'ざ', 'じ', 'ず', 'ぜ', 'ぞ', 'だ', 'ぢ', 'づ', 'で', 'ど', 'ば', 'び', 'ぶ',
+    /*  40 */ 'べ', 'ぼ', 'ぱ', 'ぴ', 'ぷ', 'ぺ', 'ぽ', '_', '_', '_', '_', '_', 'ゃ', 'っ', 'ゅ', 'ょ',
+    /*  50 */ 'ア', 'イ', 'ウ', 'エ', 'オ', 'カ', 'キ', 'ク', 'ケ', 'コ', 'サ', 'シ', 'ス', 'セ', 'ソ', 'タ',
+    /*  60 */ 'チ', 'ツ', 'テ', 'ト', 'ナ', 'ニ', 'ヌ', 'ネ', 'ノ', 'ハ', 'ヒ', 'フ', 'ヘ', 'ホ', 'マ', 'ミ',
+    /*  70 */ 'ム', 'メ', 'モ', 'ヤ', 'ユ', 'ヨ', 'ラ', 'リ', 'ル', 'レ', 'ロ', 'ワ', 'ン', 'ガ', 'ギ', 'グ',
+    /*  80 */ 'ゲ', 'ゴ', 'ザ', 'ジ', 'ズ', 'ゼ', 'ゾ', 'ダ', 'ヂ', 'ヅ', 'デ', 'ド', 'バ', 'ビ', 'ブ', 'ベ',
+    /*  90 */ 'ボ', 'パ', 'ピ', 'プ', 'ペ', 'ポ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ', 'ャ', 'ッ', 'ュ', 'ョ', '０',
+    /*  A0 */ '１', '２', '３', '４', '５', '６', '７', '８', '９', 'Ｈ', 'Ｐ', 'Ｌ', 'Ｖ', '@' /*EXのE*/, 'µ' /*EXのX*/, 'Ｍ',
+    /*  B0 */ '／', 'Ｄ', 'Ｒ', '店', 'Ｙ', 'Ｎ', 'Ｅ', 'Ｓ', 'Ａ', 'Ｗ', '％', 'Ｘ', '「', '」', '＿', '・',
+    /*  C0 */ '（', '）', ' ', 'ー', '！', '兵', '書', '石', '竜', '杖', '星', '特', '輸', '送', '隊', '状',
+    /*  D0 */ '況', '終', '断', '話', '門', '道', '魔', '運', '所', '持', '品', '捨', '交', '換', '預', '闘',
+    /*  E0 */ '技', '場', '防', '城', '装', '使', '用', '攻', '撃', '速', '守', '備', '効', '幸', '第', '章',
+    /*  F0 */ 'Ｔ', 'Ｏ', '部', '騎', '＋', '宝', '箱', 'Ｂ', '器', '回', '復', '系', '？', '士', '：', '命',
+    /* 100 */ '中', '重', '必', '殺', '武', '╔', '╦', '╗', '╠', '╣', '╚', '╩', '╝', '⇔', '❏', '╭',
+    /* 110 */ '╮', '╰', '╯', '┏','┳', '┓', '┣', '┫', '┗', '┻', '┛',
+];
+
+#[rustfmt::skip]
+pub const DIALOGUES_CHARACTERS: [[char; 256]; 4] = [
+    [   // 11
+        /* 00 */ 'X', 'あ', 'い', 'う', 'え', 'お', 'か', 'き', 'く', 'け', 'こ', 'さ', 'し', 'す', 'せ', 'そ',
+        /* 10 */ 'た', 'ち', 'つ', 'て', 'と', 'な', 'に', 'ぬ', 'ね', 'の', 'は', 'ひ', 'ふ', 'へ', 'ほ', 'ま',
+        /* 20 */ 'み', 'む', 'め', 'も', 'や', 'ゆ', 'よ', 'ら', 'り', 'る', 'れ', 'ろ', 'わ', 'を', 'ん', 'が',
+        /* 30 */ 'ぎ', 'ぐ', 'げ', 'ご', 'ざ', 'じ', 'ず', 'ぜ', 'ぞ', 'だ', 'ぢ', 'づ', 'で', 'ど', 'ば', 'び',
+        /* 40 */ 'ぶ', 'べ', 'ぼ', 'ぱ', 'ぴ', 'ぷ', 'ぺ', 'ぽ', 'ぁ', 'ぃ', '_', 'ぇ', 'ぉ', 'ゃ', 'っ', 'ゅ',
+        /* 50 */ 'ょ', 'ア', 'イ', 'ウ', 'エ', 'オ', 'カ', 'キ', 'ク', 'ケ', 'コ', 'サ', 'シ', 'ス', 'セ', 'ソ',
+        /* 60 */ 'タ', 'チ', 'ツ', 'テ', 'ト', 'ナ', 'ニ', 'ヌ', 'ネ', 'ノ', 'ハ', 'ヒ', 'フ', 'ヘ', 'ホ', 'マ',
+        /* 70 */ 'ミ', 'ム', 'メ', 'モ', 'ヤ', 'ユ', 'ヨ', 'ラ', 'リ', 'ル', 'レ', 'ロ', 'ワ', 'ン', 'ガ', 'ギ',
+        /* 80 */ 'グ', 'ゲ', 'ゴ', 'ザ', 'ジ', 'ズ', 'ゼ', 'ゾ', 'ダ', 'ヂ', 'ヅ', 'デ', 'ド', 'バ', 'ビ', 'ブ',
+        /* 90 */ 'ベ', 'ボ', 'パ', 'ピ', 'プ', 'ペ', 'ポ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ', 'ャ', 'ッ', 'ュ', 'ョ',
+        /* A0 */ '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '神', '何', '会', '賊', '大', '男',
+        /* B0 */ '元', '気', '出', '_', '今', '皇', '帝', '聖', '_', 'ー', '勇', '者', '？', '苦', '加', '争',
+        /* C0 */ '「', '」', '（', '）', ' ', '！', '攻', '撃', '力', '守', '備', '魔', '竜', '石', '団', '闘',
+        /* D0 */ '技', '場', '店', '必', '殺', '一', '道', '書', '杖', '系', 'Ｍ', '星', '話', '兵', '士', '使',
+        /* E0 */ '用', '運', '_', '法', '軍', '白', '火', '飛', '地', '暗', '黒', '進', '城', '海', '人', '騎',
+        /* F0 */ '.', '王', '子', '女', '様', '父', '行', '戦', '来', '・', '母', '山', '集', '♥', '剣', '国',
+    ],
+    [   // 12
+        /* 00 */ '_', '待', '祖', '老', '東', '方', '辺', '境', '小', '援', '助', '_', '親', '討', '身', '愛',
+        /* 10 */ '姉', '姫', '才', '々', '二', '年', '日', '対', '岸', '街', '彼', '解', '放', '島', '南', '北',
+        /* 20 */ '近', '村', '旅', '悪', '_', '知', '陸', '草', '原', '建', '以', '他', '_', '占', '領', '_',
+        /* 30 */ '名', '弟', '半', '公', '数', '_', '前', '主', '破', '同', '盟', '内', '財', '宝', '敢', '箱',
+        /* 40 */ '俺', '奴', '中', '_', '_', '下', '都', '谷', '築', '無', '砦', '最', '将', '指', '揮', '唯',
+        /* 50 */ '門', '始', '後', '退', '入', '休', '_', '_', '金', '自', '治', '好', '意', '的', '傭', '久',
+        /* 60 */ '_', '持', '願', '質', '捕', '要', '塞', '決', '罠', '言', '見', '性', '恐', '思', '上', '悲',
+        /* 70 */ '辛', '分', '代', '救', '率', '末', '千', '宮', '物', '多', '達', '目', '市', '家', '統', '合',
+        /* 80 */ '事', '世', '三', '種', '武', '器', '_', '血', '_', '貴', '_', '_', '立', '偉', '手', '_',
+        /* 90 */ '敗', '向', '_', '水', '_', '美', '荒', '迎', '全', '部', '隊', '取', '罪', '賢', '光', '殿',
+        /* A0 */ '_', '止', '英', '雄', '強', '背', '_', '未', '開', '族', '切', '従', '現', '弱', '復', '活',
+        /* B0 */ '受', '_', '誇', '滅', '亡', '古', '高', '度', '絶', '動', '塔', '司', '祭', '失', '追', '込',
+        /* C0 */ '包', '囲', '所', '突', '散', '残', '選', '命', '_', '歩', '盗', '港', '＜', '＞', '心', '着',
+        /* D0 */ '生', '足', '移', '文', '明', '望', '時', '平', '和', '支', '配', '激', '界', '秘', '野', '乱',
+        /* E0 */ '域', '_', '_', '_', '打', '倒', '、', '。', '死', '槍', '弓', '斧', '民', '位', '先', '利',
+        /* F0 */ '兄', '氷', '信', '真', '正', '義', '忠', '誠', '臣', '婚', '礼', '_', '伯', '伝', '説', '炎',
+    ],
+    [   // 13
+        /* 00 */ '_', '紋', '章', '_', '反', '断', '_', '_', '初', '_', '消', '第', '町', '隷', '天', '空',
+        /* 10 */ '_', '遠', '征', '連', '去', '嘆', '_', '_', '墓', '_', '再', '奪', '回', '_', '帰', '_',
+        /* 20 */ '壇', '制', '圧', '病', '終', '_', '相', '商', '勝', '負', '続', '表', '示', 'B', '賞', '_',
+        /* 30 */ '然', '異', '壊', '護', '熱', '識', '各', '装', '西', '妹', '安', '貨', '侵', '玉', '座', '準',
+        /* 40 */ '鉄', '銀', '屋', '百', '封', '印', '盾', '電', '間', '橋', '_', '脱', '防', '壮', '冷', '昔',
+        /* 50 */ '途', '官', '_', '狂', '_', '希', '重', '栄', '発', '_', '語', '呋', '体', '令', '精', '鋭',
+        /* 60 */ '邪', '協', '接', '完', '政', '緒', '節', '砂', '漠', '灼', '太', '陽', '当', '党', '本', '能',
+        /* 70 */ '少', '作', '幸', '理', '新', '夫', '逃', '略', '不', '永', '参', '惨', '七', '減', '成', '共',
+        /* 80 */ '訪', '_', '感', '与', '挟', '長', '具', '担', '急', '由', '耳', '産', '_', '_', '超', '仲',
+        /* 90 */ '変', '務', '償', '売', '買', '声', '教', '恋', '員', '服', '修', '屈', '注', '巨', '情', '馬',
+        /* A0 */ '徒', '森', '得', '機', '軽', '経', '験', '昇', '格', '扱', '有', '若', '涙', '効', '特', '殊',
+        /* B0 */ '離', '在', '存', '呪', '_', '仕', '御', '興', '蛮', '化', '外', '抗', '抵', '処', '刑', '_',
+        /* C0 */ '陛', '獄', '可', '個', '室', '危', '択', '結', '引', '_', '旗', '密', '_', '_', '_', '赤',
+        /* D0 */ '短', '乗', '_', '_', '_', '逆', '派', '遣', '_', '踊', '迷', '_', '学', '勉', '伐', '_',
+        /* E0 */ '限', '議', '_', '_', '果', '保', '害', '期', '_', '_', '岩', '別', '想', '_', '_', '_',
+        /* F0 */ '_', '_', '_', '_', '_', '衛', '息', '類', '_', '実', '_', '非', '術', '_', '鋼', '継',
+    ],
+    [   // 14
+        /* 00 */ '_', '承', '_', '_', '増', '_', '_', '_', '_', '風', '_', '_', '_', '_', '_', '定',
+        /* 10 */ '_', '戻', '請', '_', '_', '任', '念', '忘', '涯', '_', '頼', '姿', '_', '遊', '_', '_',
+        /* 20 */ '良', '_', '_', '師', '捨', '_', '託', '娘', '告', '_', '予', '_', '努', '焼', '役', '仮',
+        /* 30 */ '面', '暮', '我', '堂', '腕', '_', '_', '業', '五', '_', '_', '_', '_', '_', '応', '_',
+        /* 40 */ '_', '_', '_', '_', '_', '_', '_', '報', '皆', '払', '混', '双', '口', '降', '伏', '_',
+        /* 50 */ '_', '幅', '悔', '毎', '牢', '肉', '到', '_', '妃', '_', '迫', '求', '職', '魂', '紙', '_',
+        /* 60 */ '通', '供', '許', '局', '送', '絡', '孫', '扉', '約', '束', '_', '万', 'ヲ', '左', '右', '峠',
+        /* 70 */ '闇', '稼', '_', '台', '_', '_', '覇', '_', '怒', '威', '付', '_', '_', '_', '_', '_',
+        /* 80 */ '_', '_', '_', '_', '細', '_', '_', '川', '_', '_', '呼', '起', '_', '_', '泣', '欲',
+        /* 90 */ '_', '_', '_', '段', '階', '_', '_', '_', '_', '_', '_', '_', '寸', '_', '_', '_',
+        /* A0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
+        /* B0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
+        /* C0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
+        /* D0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
+        /* E0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
+        /* F0 */ '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_', '_',
+    ],
+];
+
+/// Names of the portraits, the first one being used when decompiling.
+/// Unnamed portraits are written as their ID, like `#148`.
+pub const PORTRAITS: [&[&str]; 256] = [
+    &["Marth"],
+    &["Ceada", "Caeda", "シーダ"],
+    &["Jagen"],
+    &["Cain"],
+    &["Abel"],
+    &["Draug"],
+    &["Gordin"],
+    &["Rickard"],
+    &[],
+    &["Bord"],
+    &["Barst"],
+    &["Cord"],
+    &["Castor"],
+    &["Ogma"],
+    &[],
+    &["Julian"],
+    &["Lena"],
+    &["Merric"],
+    &["Navarre"],
+    &["Hardin"],
+    &["Wolf"],
+    &["Sedgar"],
+    &["Roshea"],
+    &["Vyland"],
+    &["Bantu"],
+    &["Caesar"],
+    &["Radd"],
+    &["Midia"],
+    &["Catria"],
+    &["Maria"],
+    &["Minerva"],
+    &["Linde"],
+    &["Jeorge"],
+    &["Dolph"],
+    &["Macellan"],
+    &["Tomas"],
+    &["Boah"],
+    &["Arran"],
+    &["Samson"],
+    &[],
+    &["Xane"],
+    &["Wendell"],
+    &["Est"],
+    &["Astram"],
+    &["Matthis"],
+    &[],
+    &[],
+    &["Palla"],
+    &["Tiki"],
+    &["Lorenz"],
+    &["Elice"],
+    &["Gotoh"],
+    &["Gazzak"],
+    &[],
+    &[],
+    &["Gomer"],
+    &[],
+    &[],
+    &["Merach"],
+    &["Harmein"],
+    &["Emereus"],
+    &[],
+    &["Kannival"],
+    &[],
+    &["Zharov"],
+    &["Khozen"],
+    &[],
+    &[],
+    &["Jiol"],
+    &["Volzhin"],
+    &["Hollstadt"],
+    &[],
+    &["Hyman"],
+    &["Xemcel"],
+    &[],
+    &["Camus"],
+    &["Michalis"],
+    &["Morzas"],
+    &["Gharnef"],
+    &["Medeus"],
+    &[],
+    &["Nehring"],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &["Malledus"],
+    &["Nyna"],
+    &["King of Talys"],
+    &["King of Aurelis"],
+    &["Villager (Bald Dad #1)"],
+    &["Villager (Bald Dad #2)"],
+    &["Villager (Bald Dad #3)"],
+    &["Villager (old man)"],
+    &["Villager (old woman)"],
+    &["Villager (Uncle)"],
+    &["Villager (auntie)"],
+    &["Villager (male)"],
+    &["Villager (female)"],
+    &[],
+    &[],
+    &["Strange old woman"],
+    &[],
+    &[],
+    &["Slave Trader"],
+    &["Linda (slave)"],
+    &["Enemy Soldier"],
+    &["Cecil"],
+    &["Luke"],
+    &["Roderick"],
+    &["Ryan"],
+    &["Warren"],
+    &["Lumel"],
+    &["Lykke"],
+    &["Yuliya"],
+    &["Jubelo"],
+    &["Sirius"],
+    &["Gail"],
+    &["Marisha"],
+    &["Torus"],
+    &["Samuel"],
+    &["Lang"],
+    &["Phina"],
+    &["Dall"],
+    &["Yodel"],
+    &["Arlen"],
+    &["Eibel"],
+    &["Sheena"],
+    &["Willow"],
+    &["Marth (book 2)"],
+    &["Marth determined (book 2)"],
+    &["Linde (book 2)"],
+    &["Merric (book 2)"],
+    &["Tiki (book 2)"],
+    &["Ceada (book 2)"],
+    &["Medeus (dragon)"],
+    &["Minerva (book 2)"],
+    &["Hardin (book 2)"],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+];